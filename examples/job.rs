@@ -0,0 +1,48 @@
+/// Example usage of high level job manipulation
+use qarnot::client::QarnotClient;
+use qarnot::compute::models::Constants;
+use qarnot::config;
+
+#[tokio::main]
+async fn main() {
+    #[cfg(debug_assertions)]
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("debug")).init();
+
+    let conf = config::Config::from_file("sample.conf").expect("Failed to parse configuration");
+    let client = QarnotClient::new(conf)
+        .await
+        .expect("Could not configure Qarnot Client");
+
+    let mut job = client.create_job("hljob", None, None);
+    job.run().await.expect("failed to run job");
+
+    for i in 0..3 {
+        let mut task = job
+            .create_task(
+                &format!("hljob-task-{i}"),
+                Some("docker-batch"),
+                None,
+                1.into(),
+            )
+            .expect("job not submitted");
+        let mut constants = Constants::new();
+        constants.insert(
+            "DOCKER_REGISTRY_LOGIN",
+            &std::env::var("DOCKER_USER").expect("Missing DOCKER_USER env variable"),
+        );
+        constants.insert(
+            "DOCKER_REGISTRY_PASSWORD",
+            &std::env::var("DOCKER_PASS").expect("Missing DOCKER_PASS env variable"),
+        );
+        constants.insert("DOCKER_CMD", &format!("echo Task {i} of the job"));
+        task.constants = Some(constants);
+        task.run().await.expect("failed to run task");
+    }
+
+    let tasks = job.wait().await.expect("failed to wait for job tasks");
+    for task in tasks {
+        println!("{:?}: {:?}", task.name, task.state);
+    }
+
+    job.terminate().await.expect("failed to terminate job");
+}
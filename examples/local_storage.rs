@@ -0,0 +1,26 @@
+use qarnot::client::QarnotClient;
+use qarnot::config;
+use qarnot::storage::{LocalStorageClient, StorageObject};
+
+#[tokio::main]
+async fn main() {
+    #[cfg(debug_assertions)]
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("debug")).init();
+
+    let conf = config::Config::from_file("sample.conf").unwrap();
+    // Buckets are stored as directories of ./local_storage, no S3 endpoint needed
+    let storage = LocalStorageClient::new("local_storage");
+    let client = QarnotClient::with_storage(conf, Some(storage)).unwrap();
+
+    if client.get_bucket("testbucket").await.is_err() {
+        client.create_bucket("testbucket").await.unwrap();
+    }
+
+    let bucket = client.get_bucket("testbucket").await.unwrap();
+    let obj = StorageObject::new("sample.conf", "conf/sample.conf");
+    bucket.upload_object(obj).await.unwrap();
+
+    for object in bucket.list_objects().await.unwrap() {
+        println!("{} ({} bytes)", object.key, object.size);
+    }
+}
@@ -3,6 +3,8 @@ use qarnot::client::QarnotClient;
 use qarnot::compute::models::Constants;
 use qarnot::config;
 
+use futures_util::StreamExt;
+
 #[tokio::main]
 async fn main() {
     #[cfg(debug_assertions)]
@@ -37,10 +39,13 @@ async fn main() {
     hltask
         .uuid
         .expect("Task uuid is None, task submission probably failed");
-    hltask
-        .wait()
-        .await
-        .expect("failed to wait for task completion");
+    hltask.poll_interval = std::time::Duration::from_secs(2);
+    {
+        let mut events = std::pin::pin!(hltask.watch());
+        while let Some(event) = events.next().await {
+            println!("{:?}", event.expect("failed to watch task"));
+        }
+    }
 
     let out = hltask.stdout().await.expect("could not get task stdout");
     println!("{}", out);
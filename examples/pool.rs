@@ -0,0 +1,51 @@
+/// Example usage of high level pool manipulation
+use chrono::TimeDelta;
+use qarnot::client::QarnotClient;
+use qarnot::compute::models::Constants;
+use qarnot::config;
+
+#[tokio::main]
+async fn main() {
+    #[cfg(debug_assertions)]
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("debug")).init();
+
+    let conf = config::Config::from_file("sample.conf").expect("Failed to parse configuration");
+    let client = QarnotClient::new(conf)
+        .await
+        .expect("Could not configure Qarnot Client");
+
+    let mut pool = client.create_pool("hlpool", "docker-batch", None, 1);
+    let mut constants = Constants::new();
+    constants.insert(
+        "DOCKER_REGISTRY_LOGIN",
+        &std::env::var("DOCKER_USER").expect("Missing DOCKER_USER env variable"),
+    );
+    constants.insert(
+        "DOCKER_REGISTRY_PASSWORD",
+        &std::env::var("DOCKER_PASS").expect("Missing DOCKER_PASS env variable"),
+    );
+    pool.constants = Some(constants);
+    pool.set_elastic(0, 4, TimeDelta::seconds(90));
+
+    pool.run().await.expect("failed to run pool");
+    pool.wait_until_open()
+        .await
+        .expect("pool failed before opening");
+
+    let mut task = pool
+        .create_task("hlpool-task", None, 2.into())
+        .expect("pool not submitted");
+    let mut constants = Constants::new();
+    constants.insert("DOCKER_CMD", "echo Task in a pool");
+    task.constants = Some(constants);
+    task.run().await.expect("failed to run task");
+    task.wait()
+        .await
+        .expect("failed to wait for task completion");
+    println!(
+        "{}",
+        task.stdout().await.expect("could not get task stdout")
+    );
+
+    pool.close().await.expect("failed to close pool");
+}
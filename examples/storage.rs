@@ -1,7 +1,7 @@
 use log::{error, info};
 use qarnot::client::QarnotClient;
 use qarnot::config;
-use qarnot::storage::StorageObject;
+use qarnot::storage::{StorageClient, StorageObject};
 
 #[tokio::main]
 async fn main() {
@@ -17,7 +17,7 @@ async fn main() {
     println!("{:?}", bucket_list);
 
     if let Ok(list) = bucket_list {
-        if !list.iter().any(|b| b.name() == Some("testbucket")) {
+        if !list.iter().any(|b| b == "testbucket") {
             let bucket = client
                 .storage
                 .as_ref()
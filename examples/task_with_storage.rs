@@ -1,5 +1,6 @@
 use qarnot::client::QarnotClient;
 use qarnot::compute::models::Constants;
+use qarnot::compute::resources::TaskResources;
 use qarnot::config;
 use qarnot::storage::StorageObject;
 
@@ -14,40 +15,14 @@ async fn main() {
     // Is it up ?
     assert!(client.compute_client.get_status().await.is_ok());
 
-    // Clean previous run input/output
-    let _ = client
-        .storage
-        .as_ref()
-        .unwrap()
-        .delete_object("testinput", "examples/sample_rust.rs")
-        .await;
-    let _ = client
-        .storage
-        .as_ref()
-        .unwrap()
-        .delete_object("testinput", "examples")
-        .await;
-
     let bucket_list = client.buckets().await;
     if let Ok(list) = bucket_list {
-        if !list.iter().any(|b| b.name.as_str() == "testinput") {
-            let bucket = client.create_bucket("testinput").await;
-            println!("{:?}", bucket);
-        }
         if !list.iter().any(|b| b.name.as_str() == "testoutput") {
             let bucket = client.create_bucket("testoutput").await;
             println!("{:?}", bucket);
         }
     }
 
-    let bucket = client.get_bucket("testinput").await.unwrap();
-
-    let obj = StorageObject::new("examples/sample_rust.rs", "sample_rust.rs");
-    bucket
-        .upload_object(obj)
-        .await
-        .expect("Could not upload object");
-
     let mut task = client.create_task("Rust compile", "docker-batch".into(), None, 1.into());
 
     let mut constants = Constants::new();
@@ -64,7 +39,11 @@ async fn main() {
     constants.insert("DOCKER_CMD", "rustc --out-dir /job /job/sample_rust.rs");
 
     task.constants = Some(constants);
-    task.resouce_buckets = Some(vec![String::from("testinput")]);
+    // testinput is created if needed and sample_rust.rs only uploaded if it changed
+    let mut resources = TaskResources::new("testinput", Some("examples"));
+    resources.strip_prefix = true;
+    resources.add_file("examples/sample_rust.rs", "sample_rust.rs");
+    task.add_resources(resources);
     task.result_bucket = Some(String::from("testoutput"));
 
     task.run().await.expect("Failed to run task");
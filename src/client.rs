@@ -1,42 +1,51 @@
 use crate::compute::client::ComputeClient;
+use crate::compute::job::Job;
+use crate::compute::pool::Pool;
+use crate::compute::secrets::Secrets;
 use crate::compute::task::{InstancesOrRange, ProfileOrPool, Task};
+use crate::compute::task_builder::{TaskBuilder, ValidationError};
 use crate::compute::ComputeError;
 use crate::config;
 use crate::storage::bucket::Bucket;
-use crate::storage::StorageClient;
-use crate::storage::StorageError;
+use crate::storage::{
+    ObjectInfo, ObjectStream, S3StorageClient, StorageClient, StorageError, StorageObject,
+};
 use bytes::Bytes;
-
-use aws_sdk_s3::types::Object;
+use std::path::Path;
 
 /// Client for the Qarnot API and chosen storage API if one is requested
-pub struct QarnotClient {
+///
+/// The storage backend defaults to [`S3StorageClient`], use
+/// [`QarnotClient::with_storage`] to provide another [`StorageClient`]
+/// implementation.
+pub struct QarnotClient<S: StorageClient = S3StorageClient> {
     /// Client for the Qarnot API
     pub compute_client: ComputeClient,
-    /// Client for the S3 service for Task data
-    pub storage: Option<StorageClient>,
+    /// Client for the storage service for Task data
+    pub storage: Option<S>,
 }
 
 impl QarnotClient {
-    /// Create a new [`QarnotClient`]
+    /// Create a new [`QarnotClient`] using Qarnot's S3 storage
     ///
     /// # Arguments
     /// * `conf` - The configuration of the client to create
     /// # Errors
-    /// * `Error::ComputeApiUnauthorized` - Credentials error
-    /// * `Error::ComputeApiGeneric` - Failed to get user info from the API
+    /// * `Error::Compute(ComputeError::InvalidApiKey)` - Malformed credentials
+    /// * `Error::Compute(ComputeError::Unauthorized)` - Credentials error
+    /// * `Error::Compute(_)` - Failed to get user info from the API
     pub async fn new(conf: config::Config) -> Result<Self, Error> {
         let version = conf.version;
         let compute_url = conf.api_url;
         let api_key = conf.api_key;
-        let mut storage: Option<StorageClient> = None;
+        let mut storage: Option<S3StorageClient> = None;
         let compute_client = ComputeClient::new(compute_url, version, &api_key)?;
         if let Some(storage_url) = conf.storage_url {
             let user = compute_client.get_user_info().await?;
             info!("compute user email {:?}", user.email);
 
             if let Some(email) = &user.email {
-                storage = Some(StorageClient::new(email, &api_key, &storage_url));
+                storage = Some(S3StorageClient::new(email, &api_key, &storage_url));
             } else {
                 error!("No user email, cannot instantiate storage_client");
             }
@@ -46,109 +55,94 @@ impl QarnotClient {
             storage,
         })
     }
+}
+
+impl<S: StorageClient> QarnotClient<S> {
+    /// Create a new [`QarnotClient`] with a custom storage backend
+    ///
+    /// The `storage_url` of the configuration is ignored.
+    ///
+    /// # Arguments
+    /// * `conf` - The configuration of the client to create
+    /// * `storage` - The storage backend to use, if any
+    /// # Errors
+    /// * `Error::Compute(ComputeError::InvalidApiKey)` - Malformed credentials
+    pub fn with_storage(conf: config::Config, storage: Option<S>) -> Result<Self, Error> {
+        let compute_client = ComputeClient::new(conf.api_url, conf.version, &conf.api_key)?;
+        Ok(Self {
+            compute_client,
+            storage,
+        })
+    }
+
+    /// Storage backend, if one is configured
+    fn storage(&self) -> Result<&S, Error> {
+        self.storage.as_ref().ok_or(Error::NoStorageClient)
+    }
 
     /// List buckets
-    pub async fn buckets(&self) -> Result<Vec<Bucket>, Error> {
-        if let Some(storage) = self.storage.as_ref() {
-            let bucket_names = storage
-                .buckets()
-                .await
-                .map_err(|_| Error::StorageApiConnect)?;
-            Ok(bucket_names
-                .iter()
-                .filter(|b| b.name().is_some())
-                .map(|b| Bucket::new(storage, b.name().unwrap_or_default()))
-                .collect())
-        } else {
-            Err(Error::NoStorageClient)
-        }
+    pub async fn buckets(&self) -> Result<Vec<Bucket<'_, S>>, Error> {
+        let storage = self.storage()?;
+        let bucket_names = storage.buckets().await?;
+        Ok(bucket_names
+            .iter()
+            .map(|name| Bucket::new(storage, name))
+            .collect())
     }
 
     /// Get an existing bucket
-    pub async fn get_bucket(&self, bucket_name: &str) -> Result<Bucket, Error> {
-        if let Some(storage) = self.storage.as_ref() {
-            let bucket_names = storage
-                .buckets()
-                .await
-                .map_err(|_| Error::StorageApiConnect)?;
-            let bucket = bucket_names
-                .iter()
-                .find(|b| b.name().map_or(false, |n| n == bucket_name));
-            bucket.map_or(Err(Error::NoSuchBucket), |_| {
-                Ok(Bucket::new(storage, bucket_name))
-            })
-        } else {
-            Err(Error::NoStorageClient)
-        }
+    pub async fn get_bucket(&self, bucket_name: &str) -> Result<Bucket<'_, S>, Error> {
+        let storage = self.storage()?;
+        let bucket_names = storage.buckets().await?;
+        let bucket = bucket_names.iter().find(|name| *name == bucket_name);
+        bucket.map_or(Err(Error::NoSuchBucket), |_| {
+            Ok(Bucket::new(storage, bucket_name))
+        })
     }
 
     /// Create a new bucket
     pub async fn create_bucket(&self, name: &str) -> Result<(), Error> {
-        if let Some(storage) = self.storage.as_ref() {
-            storage
-                .create_bucket(name)
-                .await
-                .map_err(|_| Error::StorageApiConnect)
-        } else {
-            Err(Error::NoStorageClient)
-        }
+        Ok(self.storage()?.create_bucket(name).await?)
     }
 
     /// Delete a bucket
     pub async fn delete_bucket(&self, name: &str) -> Result<(), Error> {
-        if let Some(storage) = self.storage.as_ref() {
-            storage
-                .delete_bucket(name)
-                .await
-                .map_err(|_| Error::StorageApiConnect)
-        } else {
-            Err(Error::NoStorageClient)
-        }
+        Ok(self.storage()?.delete_bucket(name).await?)
     }
 
     /// List objects in a bucket
-    pub async fn list_objects(&self, bucket: &str) -> Result<Vec<Object>, Error> {
-        if let Some(storage) = self.storage.as_ref() {
-            storage
-                .list_objects(bucket)
-                .await
-                .map_err(|_| Error::StorageApiConnect)
-        } else {
-            Err(Error::NoStorageClient)
-        }
+    pub async fn list_objects(&self, bucket: &str) -> Result<Vec<ObjectInfo>, Error> {
+        Ok(self.storage()?.list_objects(bucket).await?)
     }
 
     /// Upload an object to a bucket
-    pub async fn upload_object(
-        &self,
-        bucket: &str,
-        object: crate::storage::StorageObject,
-    ) -> Result<(), Error> {
-        if let Some(storage) = self.storage.as_ref() {
-            storage
-                .upload_object(bucket, object)
-                .await
-                .map_err(|_| Error::StorageApiConnect)
-        } else {
-            Err(Error::NoStorageClient)
-        }
+    pub async fn upload_object(&self, bucket: &str, object: StorageObject) -> Result<(), Error> {
+        Ok(self.storage()?.upload_object(bucket, object).await?)
     }
 
     /// Returns Bytes of an object in a bucket
+    /// The whole object is held in memory, use `download_object` or
+    /// `get_object_stream` for large objects.
     pub async fn get_object(&self, bucket: &str, key: &str) -> Result<Bytes, Error> {
-        if let Some(storage) = self.storage.as_ref() {
-            let stream = storage
-                .get_object(bucket, key)
-                .await
-                .map_err(|_| Error::StorageApiConnect)?;
-            let bytes = stream
-                .collect()
-                .await
-                .map_err(|_| Error::StorageObjectDownload)?;
-            Ok(bytes.into_bytes())
-        } else {
-            Err(Error::NoStorageClient)
-        }
+        Ok(self.storage()?.get_object(bucket, key).await?)
+    }
+
+    /// Returns a reader over the content of an object in a bucket
+    pub async fn get_object_stream(&self, bucket: &str, key: &str) -> Result<ObjectStream, Error> {
+        Ok(self.storage()?.get_object_stream(bucket, key).await?)
+    }
+
+    /// Stream an object of a bucket to `local_path`, returning its size
+    pub async fn download_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        local_path: &Path,
+    ) -> Result<u64, Error> {
+        Ok(self
+            .storage()?
+            .download_object(bucket, key, local_path)
+            .await?)
     }
 
     /// Creates a new task and returns `compute::Task` struct
@@ -159,14 +153,65 @@ impl QarnotClient {
         profile_or_pool: ProfileOrPool,
         shortname: Option<String>,
         instance_or_range: InstancesOrRange,
-    ) -> Task {
-        Task::new(
+    ) -> Task<'_> {
+        let mut task = Task::new(
             &self.compute_client,
             name,
             profile_or_pool,
             shortname,
             instance_or_range,
-        )
+        );
+        task.set_storage(self.storage.as_ref().map(|s| s as &dyn StorageClient));
+        task
+    }
+
+    /// Start building a task, validated before being returned
+    #[must_use]
+    pub fn task_builder(&self, name: &str) -> TaskBuilder<'_> {
+        let builder = TaskBuilder::new(&self.compute_client, name);
+        match &self.storage {
+            Some(storage) => builder.storage(storage),
+            None => builder,
+        }
+    }
+
+    /// Access the secrets store of the account
+    #[must_use]
+    pub const fn secrets(&self) -> Secrets<'_> {
+        Secrets::new(&self.compute_client)
+    }
+
+    /// Creates a new job and returns `compute::Job` struct
+    #[must_use]
+    pub fn create_job(
+        &self,
+        name: &str,
+        pool: Option<uuid::Uuid>,
+        shortname: Option<String>,
+    ) -> Job<'_> {
+        let mut job = Job::new(&self.compute_client, name, pool, shortname);
+        job.set_storage(self.storage.as_ref().map(|s| s as &dyn StorageClient));
+        job
+    }
+
+    /// Creates a new pool and returns `compute::Pool` struct
+    #[must_use]
+    pub fn create_pool(
+        &self,
+        name: &str,
+        profile: &str,
+        shortname: Option<String>,
+        instance_count: i32,
+    ) -> Pool<'_> {
+        let mut pool = Pool::new(
+            &self.compute_client,
+            name,
+            profile,
+            shortname,
+            instance_count,
+        );
+        pool.set_storage(self.storage.as_ref().map(|s| s as &dyn StorageClient));
+        pool
     }
 }
 
@@ -177,11 +222,12 @@ impl QarnotClient {
 pub enum Error {
     Compute(ComputeError),
     Storage(StorageError),
-    StorageApiAuth,
-    StorageApiConnect,
-    StorageObjectDownload,
     NoStorageClient,
     NoSuchBucket,
+    /// The field cannot be modified once the task is submitted
+    ImmutableField(&'static str),
+    /// The task breaks rules of the API, it was not submitted
+    Validation(ValidationError),
 }
 
 impl From<ComputeError> for Error {
@@ -189,3 +235,9 @@ impl From<ComputeError> for Error {
         Self::Compute(compute_error)
     }
 }
+
+impl From<StorageError> for Error {
+    fn from(storage_error: StorageError) -> Self {
+        Self::Storage(storage_error)
+    }
+}
@@ -1,12 +1,27 @@
 use crate::compute::models;
 use crate::compute::models::UserInfo;
 use crate::compute::models::Version;
-use crate::compute::ComputeError;
+use crate::compute::retry::{self, RetryPolicy};
+use crate::compute::{ApiError, ComputeError};
+use crate::pagination::paginate;
+use futures_util::Stream;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use reqwest::header;
-use reqwest::{Response, StatusCode};
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::collections::HashMap;
 
+/// Default number of items per page of the paginated routes
+const DEFAULT_PAGE_SIZE: u32 = 50;
+
+/// Characters escaped in a path segment, everything but the unreserved ones
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
 const APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
 
 pub struct ComputeClient {
@@ -16,6 +31,52 @@ pub struct ComputeClient {
     compute_url: String,
     /// Compute API version
     version: String,
+    /// Retry policy of the requests
+    retry_policy: RetryPolicy,
+}
+
+/// Successful response of the compute API, along with the request it answers
+/// so that deserialization errors can be reported with their context
+struct ApiResponse {
+    response: Response,
+    method: Method,
+    route: String,
+}
+
+impl ApiResponse {
+    fn status(&self) -> StatusCode {
+        self.response.status()
+    }
+
+    /// Deserialize the response body
+    async fn json<T: DeserializeOwned>(self) -> Result<T, ComputeError> {
+        let method = self.method.clone();
+        let route = self.route.clone();
+        let body = self.text().await?;
+        serde_json::from_str::<T>(&body).map_err(|e| {
+            error!("deserialize error {}", e);
+            ComputeError::Deserialize {
+                method,
+                route,
+                body,
+                source: e,
+            }
+        })
+    }
+
+    /// Get the response body as text
+    async fn text(self) -> Result<String, ComputeError> {
+        let Self {
+            response,
+            method,
+            route,
+        } = self;
+        response.text().await.map_err(|e| ComputeError::Transport {
+            method,
+            route,
+            source: e,
+        })
+    }
 }
 
 impl ComputeClient {
@@ -26,32 +87,135 @@ impl ComputeClient {
             header::HeaderValue::from_static("application/json"),
         );
         let mut auth_value =
-            header::HeaderValue::from_str(api_key).map_err(|_| ComputeError::Unauthorized)?;
+            header::HeaderValue::from_str(api_key).map_err(|_| ComputeError::InvalidApiKey)?;
         auth_value.set_sensitive(true);
         headers.insert(header::AUTHORIZATION, auth_value);
         let client = reqwest::Client::builder()
             .user_agent(APP_USER_AGENT)
             .default_headers(headers)
             .build()
-            .map_err(|_| ComputeError::Generic)?;
+            .map_err(ComputeError::Client)?;
 
         Ok(Self {
             client,
             compute_url,
             version,
+            retry_policy: RetryPolicy::default(),
         })
     }
 
-    fn check_status(response: Response) -> Result<Response, ComputeError> {
-        match response.status() {
-            StatusCode::OK => Ok(response),
-            StatusCode::UNAUTHORIZED => Err(ComputeError::Unauthorized),
-            StatusCode::FORBIDDEN => Err(ComputeError::Forbidden),
-            StatusCode::NOT_FOUND => Err(ComputeError::NotFound),
-            _ => Err(ComputeError::Generic),
+    /// Set the retry policy applied to every request
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+
+    /// Current retry policy of the client
+    pub const fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Turn non successful (non 2xx) responses into the matching [`ComputeError`]
+    async fn check_status(
+        response: Response,
+        method: Method,
+        route: &str,
+    ) -> Result<ApiResponse, ComputeError> {
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(retry::parse_retry_after);
+        if status.is_success() {
+            return Ok(ApiResponse {
+                response,
+                method,
+                route: route.to_owned(),
+            });
+        }
+        let body = response.text().await.unwrap_or_default();
+        let error = Box::new(ApiError {
+            status,
+            method,
+            route: route.to_owned(),
+            error: serde_json::from_str(&body).ok(),
+            body,
+            retry_after,
+        });
+        error!("API Error: {}", error);
+        Err(match status {
+            StatusCode::BAD_REQUEST => ComputeError::Validation(error),
+            StatusCode::UNAUTHORIZED => ComputeError::Unauthorized(error),
+            StatusCode::FORBIDDEN => ComputeError::Forbidden(error),
+            StatusCode::NOT_FOUND => ComputeError::NotFound(error),
+            StatusCode::CONFLICT => ComputeError::Conflict(error),
+            StatusCode::TOO_MANY_REQUESTS => ComputeError::RateLimited(error),
+            _ => ComputeError::Api(error),
+        })
+    }
+
+    /// Send a request and check the response status, retrying it according
+    /// to the client [`RetryPolicy`]
+    ///
+    /// # Arguments
+    /// * `idempotent` - Whether the request can be sent again after a failure
+    ///   that may have been processed by the API (5xx, broken connection)
+    async fn send(
+        &self,
+        request: RequestBuilder,
+        method: Method,
+        route: &str,
+        idempotent: bool,
+    ) -> Result<ApiResponse, ComputeError> {
+        let mut attempt = 1;
+        loop {
+            // Requests with a streamed body cannot be cloned, hence not retried
+            let Some(current) = request.try_clone() else {
+                return self.send_once(request, method, route).await;
+            };
+            let result = self.send_once(current, method.clone(), route).await;
+            match result {
+                Err(e)
+                    if attempt < self.retry_policy.max_attempts
+                        && self.retry_policy.should_retry(&e, idempotent) =>
+                {
+                    let delay = self.retry_policy.delay(attempt, &e);
+                    warn!(
+                        "attempt {}/{} failed, retrying in {:?}: {}",
+                        attempt, self.retry_policy.max_attempts, delay, e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn send_once(
+        &self,
+        request: RequestBuilder,
+        method: Method,
+        route: &str,
+    ) -> Result<ApiResponse, ComputeError> {
+        let response = request.send().await;
+        match response {
+            Ok(response) => Self::check_status(response, method, route).await,
+            Err(e) => {
+                error!("API Error: {}", e);
+                Err(ComputeError::Transport {
+                    method,
+                    route: route.to_owned(),
+                    source: e,
+                })
+            }
         }
     }
 
+    fn url(&self, route: &str) -> String {
+        format!("{}/{}/{}", self.compute_url, self.version, route)
+    }
+
     /// Send a GET request
     ///
     /// # Arguments
@@ -61,36 +225,55 @@ impl ComputeClient {
         &self,
         route: &str,
         query: Option<Vec<(&str, &str)>>,
-    ) -> Result<reqwest::Response, ComputeError> {
-        let mut request = self
-            .client
-            .get(format!("{}/{}/{}", self.compute_url, self.version, route));
+    ) -> Result<ApiResponse, ComputeError> {
+        let mut request = self.client.get(self.url(route));
 
         if let Some(query) = query {
             request = request.query(&query);
         }
-        let response = request.send().await;
-        match response {
-            Ok(response) => Self::check_status(response),
-            Err(e) => {
-                error!("API Error: {}", e);
-                Err(ComputeError::Generic)
-            }
-        }
+        self.send(request, Method::GET, route, true).await
     }
 
+    /// Send a POST request that must not be sent twice (resource creation...)
+    ///
+    /// It is only retried when the API did not process it
     async fn post_request<T>(
         &self,
         route: &str,
         body: Option<T>,
         query: Option<Vec<(&str, &str)>>,
-    ) -> Result<reqwest::Response, ComputeError>
+    ) -> Result<ApiResponse, ComputeError>
+    where
+        T: Serialize + Send,
+    {
+        self.post(route, body, query, false).await
+    }
+
+    /// Send a POST request that can safely be sent several times
+    /// (abort, close, output retrieval...)
+    async fn safe_post_request<T>(
+        &self,
+        route: &str,
+        body: Option<T>,
+        query: Option<Vec<(&str, &str)>>,
+    ) -> Result<ApiResponse, ComputeError>
+    where
+        T: Serialize + Send,
+    {
+        self.post(route, body, query, true).await
+    }
+
+    async fn post<T>(
+        &self,
+        route: &str,
+        body: Option<T>,
+        query: Option<Vec<(&str, &str)>>,
+        idempotent: bool,
+    ) -> Result<ApiResponse, ComputeError>
     where
         T: Serialize + Send,
     {
-        let mut request = self
-            .client
-            .post(format!("{}/{}/{}", self.compute_url, self.version, route));
+        let mut request = self.client.post(self.url(route));
 
         if let Some(query) = query {
             request = request.query(&query);
@@ -100,14 +283,7 @@ impl ComputeClient {
             request = request.json(&body);
         }
 
-        let response = request.send().await;
-        match response {
-            Ok(response) => Self::check_status(response),
-            Err(e) => {
-                error!("API Error: {}", e);
-                Err(ComputeError::Generic)
-            }
-        }
+        self.send(request, Method::POST, route, idempotent).await
     }
 
     async fn put_request<T>(
@@ -115,13 +291,11 @@ impl ComputeClient {
         route: &str,
         body: Option<T>,
         query: Option<Vec<(&str, &str)>>,
-    ) -> Result<reqwest::Response, ComputeError>
+    ) -> Result<ApiResponse, ComputeError>
     where
         T: Serialize + Send,
     {
-        let mut request = self
-            .client
-            .put(format!("{}/{}/{}", self.compute_url, self.version, route));
+        let mut request = self.client.put(self.url(route));
 
         if let Some(query) = query {
             request = request.query(&query);
@@ -131,44 +305,29 @@ impl ComputeClient {
             request = request.json(&body);
         }
 
-        let response = request.send().await;
-        match response {
-            Ok(response) => Self::check_status(response),
-            Err(e) => {
-                error!("API Error: {}", e);
-                Err(ComputeError::Generic)
-            }
-        }
+        self.send(request, Method::PUT, route, true).await
     }
 
-    async fn patch_request(&self, route: &str) -> Result<reqwest::Response, ComputeError> {
-        let response = self
-            .client
-            .patch(format!("{}/{}/{}", self.compute_url, self.version, route))
-            .send()
-            .await;
-        match response {
-            Ok(response) => Self::check_status(response),
-            Err(e) => {
-                error!("API Error: {}", e);
-                Err(ComputeError::Generic)
-            }
+    async fn patch_request<T>(
+        &self,
+        route: &str,
+        body: Option<T>,
+    ) -> Result<ApiResponse, ComputeError>
+    where
+        T: Serialize + Send,
+    {
+        let mut request = self.client.patch(self.url(route));
+
+        if let Some(body) = body {
+            request = request.json(&body);
         }
+
+        self.send(request, Method::PATCH, route, true).await
     }
 
-    async fn delete_request(&self, route: &str) -> Result<reqwest::Response, ComputeError> {
-        let response = self
-            .client
-            .delete(format!("{}/{}/{}", self.compute_url, self.version, route))
-            .send()
-            .await;
-        match response {
-            Ok(response) => Self::check_status(response),
-            Err(e) => {
-                error!("API Error: {}", e);
-                Err(ComputeError::Generic)
-            }
-        }
+    async fn delete_request(&self, route: &str) -> Result<ApiResponse, ComputeError> {
+        let request = self.client.delete(self.url(route));
+        self.send(request, Method::DELETE, route, true).await
     }
 
     /// Get info on the current user
@@ -178,14 +337,7 @@ impl ComputeClient {
     pub async fn get_user_info(&self) -> Result<UserInfo, ComputeError> {
         let resp = self.get_request("info", None).await?;
         debug!("reponse status {}", resp.status());
-        let info = resp.json::<UserInfo>().await;
-        match info {
-            Ok(info) => Ok(info),
-            Err(e) => {
-                error!("deserialize error {}", e);
-                Err(ComputeError::Generic)
-            }
-        }
+        resp.json::<UserInfo>().await
     }
 
     /// Get list of API versions
@@ -194,14 +346,7 @@ impl ComputeClient {
     /// * `self` - The [`ComputeClient`]
     pub async fn get_versions(&self) -> Result<Vec<Version>, ComputeError> {
         let resp = self.get_request("versions", None).await?;
-        let versions = resp.json::<Vec<Version>>().await;
-        match versions {
-            Ok(versions) => Ok(versions),
-            Err(e) => {
-                error!("deserialize error {}", e);
-                Err(ComputeError::Generic)
-            }
-        }
+        resp.json::<Vec<Version>>().await
     }
 
     /// Get API status (and verify user auth)
@@ -231,14 +376,7 @@ impl ComputeClient {
             );
         }
         let resp = self.get_request("/tasks", query).await?;
-        let tasks = resp.json::<Vec<models::TaskOutput>>().await;
-        match tasks {
-            Ok(tasks) => Ok(tasks),
-            Err(e) => {
-                error!("deserialize error {}", e);
-                Err(ComputeError::Generic)
-            }
-        }
+        resp.json::<Vec<models::TaskOutput>>().await
     }
 
     /// Get all user tasks' summaries
@@ -259,14 +397,111 @@ impl ComputeClient {
             );
         }
         let resp = self.get_request("/tasks/summaries", query).await?;
-        let tasks = resp.json::<Vec<models::TaskSummaryOutput>>().await;
-        match tasks {
-            Ok(tasks) => Ok(tasks),
-            Err(e) => {
-                error!("deserialize error {}", e);
-                Err(ComputeError::Generic)
-            }
+        resp.json::<Vec<models::TaskSummaryOutput>>().await
+    }
+
+    /// Get a page of user tasks
+    ///
+    /// # Arguments
+    /// * `self` - The [`ComputeClient`]
+    /// * `tags` - A slice of [`&str`] for filtering tasks by tags
+    /// * `max_results` - Maximum number of tasks of the page
+    /// * `next_page_token` - Token of the page to get, `None` for the first one
+    pub async fn get_tasks_page(
+        &self,
+        tags: Option<&[&str]>,
+        max_results: Option<u32>,
+        next_page_token: Option<&str>,
+    ) -> Result<models::PaginatedResponse<models::TaskOutput>, ComputeError> {
+        let resp = self
+            .get_page("tasks/paginate", tags, max_results, next_page_token)
+            .await?;
+        resp.json::<models::PaginatedResponse<models::TaskOutput>>()
+            .await
+    }
+
+    /// Get a page of user tasks' summaries
+    ///
+    /// # Arguments
+    /// * `self` - The [`ComputeClient`]
+    /// * `tags` - A slice of [`&str`] for filtering tasks by tags
+    /// * `max_results` - Maximum number of tasks of the page
+    /// * `next_page_token` - Token of the page to get, `None` for the first one
+    pub async fn get_tasks_summaries_page(
+        &self,
+        tags: Option<&[&str]>,
+        max_results: Option<u32>,
+        next_page_token: Option<&str>,
+    ) -> Result<models::PaginatedResponse<models::TaskSummaryOutput>, ComputeError> {
+        let resp = self
+            .get_page(
+                "tasks/summaries/paginate",
+                tags,
+                max_results,
+                next_page_token,
+            )
+            .await?;
+        resp.json::<models::PaginatedResponse<models::TaskSummaryOutput>>()
+            .await
+    }
+
+    /// Send a GET request to a token paginated route
+    async fn get_page(
+        &self,
+        route: &str,
+        tags: Option<&[&str]>,
+        max_results: Option<u32>,
+        next_page_token: Option<&str>,
+    ) -> Result<ApiResponse, ComputeError> {
+        let max_results = max_results.unwrap_or(DEFAULT_PAGE_SIZE).to_string();
+        let mut query = vec![("maximumResults", max_results.as_str())];
+        if let Some(token) = next_page_token {
+            query.push(("nextPageToken", token));
         }
+        if let Some(tags) = tags {
+            query.extend(tags.iter().map(|e| ("tag", *e)));
+        }
+        self.get_request(route, Some(query)).await
+    }
+
+    /// Stream all user tasks, fetching them page by page
+    ///
+    /// # Arguments
+    /// * `self` - The [`ComputeClient`]
+    /// * `tags` - A slice of [`&str`] for filtering tasks by tags
+    /// * `page_size` - Number of tasks fetched per request
+    pub fn tasks_stream<'a>(
+        &'a self,
+        tags: Option<&'a [&'a str]>,
+        page_size: Option<u32>,
+    ) -> impl Stream<Item = Result<models::TaskOutput, ComputeError>> + 'a {
+        paginate(None, move |token: Option<String>| async move {
+            let page = self
+                .get_tasks_page(tags, page_size, token.as_deref())
+                .await?;
+            let next = page.next_token().map(|t| Some(t.to_owned()));
+            Ok((page.data, next))
+        })
+    }
+
+    /// Stream all user tasks' summaries, fetching them page by page
+    ///
+    /// # Arguments
+    /// * `self` - The [`ComputeClient`]
+    /// * `tags` - A slice of [`&str`] for filtering tasks by tags
+    /// * `page_size` - Number of tasks fetched per request
+    pub fn tasks_summaries_stream<'a>(
+        &'a self,
+        tags: Option<&'a [&'a str]>,
+        page_size: Option<u32>,
+    ) -> impl Stream<Item = Result<models::TaskSummaryOutput, ComputeError>> + 'a {
+        paginate(None, move |token: Option<String>| async move {
+            let page = self
+                .get_tasks_summaries_page(tags, page_size, token.as_deref())
+                .await?;
+            let next = page.next_token().map(|t| Some(t.to_owned()));
+            Ok((page.data, next))
+        })
     }
 
     /// Get information of the specified task
@@ -278,14 +513,7 @@ impl ComputeClient {
         uuid: uuid::Uuid,
     ) -> Result<models::TaskOutput, ComputeError> {
         let resp = self.get_request(&format!("tasks/{uuid}"), None).await?;
-        let task = resp.json::<models::TaskOutput>().await;
-        match task {
-            Ok(task) => Ok(task),
-            Err(e) => {
-                error!("deserialize error {}", e);
-                Err(ComputeError::Generic)
-            }
-        }
+        resp.json::<models::TaskOutput>().await
     }
 
     /// Get hardware constraints
@@ -296,14 +524,44 @@ impl ComputeClient {
         &self,
     ) -> Result<models::HardwareConstraintResponse, ComputeError> {
         let resp = self.get_request("hardware-constraints", None).await?;
-        let hwc = resp.json::<models::HardwareConstraintResponse>().await;
-        match hwc {
-            Ok(hwc) => Ok(hwc),
-            Err(e) => {
-                error!("deserialize error {}", e);
-                Err(ComputeError::Generic)
-            }
-        }
+        resp.json::<models::HardwareConstraintResponse>().await
+    }
+
+    /// Get a page of hardware constraints
+    ///
+    /// # Arguments
+    /// * `self` - The [`ComputeClient`]
+    /// * `offset` - Number of constraints to skip
+    /// * `limit` - Maximum number of constraints of the page
+    pub async fn get_hardware_constraints_page(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> Result<models::HardwareConstraintResponse, ComputeError> {
+        let offset = offset.to_string();
+        let limit = limit.to_string();
+        let query = vec![("offset", offset.as_str()), ("limit", limit.as_str())];
+        let resp = self
+            .get_request("hardware-constraints", Some(query))
+            .await?;
+        resp.json::<models::HardwareConstraintResponse>().await
+    }
+
+    /// Stream all hardware constraints, fetching them page by page
+    ///
+    /// # Arguments
+    /// * `self` - The [`ComputeClient`]
+    /// * `page_size` - Number of constraints fetched per request
+    pub fn hardware_constraints_stream(
+        &self,
+        page_size: Option<u32>,
+    ) -> impl Stream<Item = Result<models::HardwareConstraintVariant, ComputeError>> + '_ {
+        let limit = page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+        paginate(0, move |offset: u32| async move {
+            let page = self.get_hardware_constraints_page(offset, limit).await?;
+            let next = page.next_offset(offset, limit);
+            Ok((page.data.unwrap_or_default(), next))
+        })
     }
 
     /// Get profiles
@@ -312,14 +570,7 @@ impl ComputeClient {
     /// * `self` - The [`ComputeClient`]
     pub async fn get_profiles(&self) -> Result<Vec<String>, ComputeError> {
         let resp = self.get_request("profiles", None).await?;
-        let profiles = resp.json::<Vec<String>>().await;
-        match profiles {
-            Ok(profiles) => Ok(profiles),
-            Err(e) => {
-                error!("deserialize error {}", e);
-                Err(ComputeError::Generic)
-            }
-        }
+        resp.json::<Vec<String>>().await
     }
 
     /// Get profile details
@@ -332,14 +583,7 @@ impl ComputeClient {
     ) -> Result<models::Profile, ComputeError> {
         let path = format!("profiles/{profile_name}");
         let resp = self.get_request(&path, None).await?;
-        let profile = resp.json::<models::Profile>().await;
-        match profile {
-            Ok(profile) => Ok(profile),
-            Err(e) => {
-                error!("deserialize error {}", e);
-                Err(ComputeError::Generic)
-            }
-        }
+        resp.json::<models::Profile>().await
     }
 
     /// Get public settings
@@ -348,14 +592,7 @@ impl ComputeClient {
     /// * `self` - The [`ComputeClient`]
     pub async fn get_settings(&self) -> Result<HashMap<String, String>, ComputeError> {
         let resp = self.get_request("settings", None).await?;
-        let settings = resp.json::<HashMap<String, String>>().await;
-        match settings {
-            Ok(settings) => Ok(settings),
-            Err(e) => {
-                error!("deserialize error {}", e);
-                Err(ComputeError::Generic)
-            }
-        }
+        resp.json::<HashMap<String, String>>().await
     }
 
     /// Start a new task
@@ -368,14 +605,7 @@ impl ComputeClient {
         task: models::TaskCreationInput,
     ) -> Result<models::Id, ComputeError> {
         let resp = self.post_request("tasks", Some(task), None).await?;
-        let res = resp.json::<models::Id>().await;
-        match res {
-            Ok(uuid) => Ok(uuid),
-            Err(e) => {
-                error!("deserialize error {}", e);
-                Err(ComputeError::Generic)
-            }
-        }
+        resp.json::<models::Id>().await
     }
 
     /// Retry a task
@@ -392,14 +622,7 @@ impl ComputeClient {
         let resp = self
             .post_request(&format!("tasks/{uuid}/retry"), Some(task), None)
             .await?;
-        let res = resp.json::<models::Id>().await;
-        match res {
-            Ok(uuid) => Ok(uuid),
-            Err(e) => {
-                error!("deserialize error {}", e);
-                Err(ComputeError::Generic)
-            }
-        }
+        resp.json::<models::Id>().await
     }
 
     /// Recover a task
@@ -416,14 +639,7 @@ impl ComputeClient {
         let resp = self
             .post_request(&format!("tasks/{uuid}/recover"), Some(task), None)
             .await?;
-        let res = resp.json::<models::Id>().await;
-        match res {
-            Ok(uuid) => Ok(uuid),
-            Err(e) => {
-                error!("deserialize error {}", e);
-                Err(ComputeError::Generic)
-            }
-        }
+        resp.json::<models::Id>().await
     }
 
     /// Resume a task
@@ -440,14 +656,24 @@ impl ComputeClient {
         let resp = self
             .post_request(&format!("tasks/{uuid}/resume"), Some(task), None)
             .await?;
-        let res = resp.json::<models::Id>().await;
-        match res {
-            Ok(uuid) => Ok(uuid),
-            Err(e) => {
-                error!("deserialize error {}", e);
-                Err(ComputeError::Generic)
-            }
-        }
+        resp.json::<models::Id>().await
+    }
+
+    /// Clone a task
+    ///
+    /// # Arguments
+    /// * `self` - the [`ComputeClient`]
+    /// * `uuid` - Uuid of the task to clone
+    /// * `task` - the [`TaskCloneInput`]
+    pub async fn post_clone_task(
+        &self,
+        uuid: uuid::Uuid,
+        task: models::TaskCloneInput,
+    ) -> Result<models::Id, ComputeError> {
+        let resp = self
+            .post_request(&format!("tasks/{uuid}/clone"), Some(task), None)
+            .await?;
+        resp.json::<models::Id>().await
     }
 
     /// Update a running task
@@ -486,7 +712,9 @@ impl ComputeClient {
     /// * `self` - the [`ComputeClient`]
     /// * `uuid` - Uuid of the task
     pub async fn patch_update_task_resources(&self, uuid: uuid::Uuid) -> Result<(), ComputeError> {
-        let resp = self.patch_request(&format!("tasks/{uuid}")).await;
+        let resp = self
+            .patch_request::<()>(&format!("tasks/{uuid}"), None)
+            .await;
         match resp {
             Ok(_) => Ok(()),
             Err(e) => {
@@ -512,6 +740,56 @@ impl ComputeClient {
         }
     }
 
+    /// Take snapshots of a running task periodically
+    ///
+    /// # Arguments
+    /// * `self` - the [`ComputeClient`]
+    /// * `uuid` - Uuid of the task
+    /// * `snapshot` - the [`PeriodicSnapshot`](models::PeriodicSnapshot)
+    pub async fn post_periodic_snapshot(
+        &self,
+        uuid: uuid::Uuid,
+        snapshot: models::PeriodicSnapshot,
+    ) -> Result<(), ComputeError> {
+        let resp = self
+            .safe_post_request(
+                &format!("tasks/{uuid}/snapshot/periodic"),
+                Some(snapshot),
+                None,
+            )
+            .await;
+        match resp {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to start periodic snapshots {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Take a single snapshot of a running task
+    ///
+    /// # Arguments
+    /// * `self` - the [`ComputeClient`]
+    /// * `uuid` - Uuid of the task
+    /// * `snapshot` - the [`UniqueSnapshot`](models::UniqueSnapshot)
+    pub async fn post_unique_snapshot(
+        &self,
+        uuid: uuid::Uuid,
+        snapshot: models::UniqueSnapshot,
+    ) -> Result<(), ComputeError> {
+        let resp = self
+            .safe_post_request(&format!("tasks/{uuid}/snapshot"), Some(snapshot), None)
+            .await;
+        match resp {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to snapshot task {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
     /// Abort a task
     ///
     /// # Arguments
@@ -519,7 +797,7 @@ impl ComputeClient {
     /// * `uuid` - Uuid of the task to abort
     pub async fn post_abort_task(&self, uuid: uuid::Uuid) -> Result<(), ComputeError> {
         let resp = self
-            .post_request::<()>(&format!("tasks/{uuid}/abort"), Some(()), None)
+            .safe_post_request::<()>(&format!("tasks/{uuid}/abort"), Some(()), None)
             .await;
         match resp {
             Ok(_) => Ok(()),
@@ -530,6 +808,313 @@ impl ComputeClient {
         }
     }
 
+    /// Start a new job
+    ///
+    /// # Arguments
+    /// * `self` - The [`ComputeClient`]
+    /// * `job` - the job to create as [`JobCreationInput`]
+    pub async fn post_job(
+        &self,
+        job: models::JobCreationInput,
+    ) -> Result<models::Id, ComputeError> {
+        let resp = self.post_request("jobs", Some(job), None).await?;
+        resp.json::<models::Id>().await
+    }
+
+    /// Get all user jobs
+    ///
+    /// # Arguments
+    /// * `self` - The [`ComputeClient`]
+    pub async fn get_jobs(&self) -> Result<Vec<models::JobOutput>, ComputeError> {
+        let resp = self.get_request("jobs", None).await?;
+        resp.json::<Vec<models::JobOutput>>().await
+    }
+
+    /// Get information of the specified job
+    ///
+    /// # Arguments
+    /// * `self` - The [`ComputeClient`]
+    /// * `uuid` - Uuid of the job
+    pub async fn get_job_info(&self, uuid: uuid::Uuid) -> Result<models::JobOutput, ComputeError> {
+        let resp = self.get_request(&format!("jobs/{uuid}"), None).await?;
+        resp.json::<models::JobOutput>().await
+    }
+
+    /// Get all tasks of the specified job
+    ///
+    /// # Arguments
+    /// * `self` - The [`ComputeClient`]
+    /// * `uuid` - Uuid of the job
+    pub async fn get_job_tasks(
+        &self,
+        uuid: uuid::Uuid,
+    ) -> Result<Vec<models::TaskOutput>, ComputeError> {
+        let resp = self
+            .get_request(&format!("jobs/{uuid}/tasks"), None)
+            .await?;
+        resp.json::<Vec<models::TaskOutput>>().await
+    }
+
+    /// Terminate a job (abort all its tasks)
+    ///
+    /// # Arguments
+    /// * `self` - the [`ComputeClient`]
+    /// * `uuid` - Uuid of the job to terminate
+    pub async fn post_terminate_job(&self, uuid: uuid::Uuid) -> Result<(), ComputeError> {
+        let resp = self
+            .safe_post_request::<()>(&format!("jobs/{uuid}/terminate"), Some(()), None)
+            .await;
+        match resp {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to terminate job {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Delete a job (terminate if needed)
+    ///
+    /// # Arguments
+    /// * `self` - the [`ComputeClient`]
+    /// * `uuid` - Uuid of the job to delete
+    pub async fn delete_job(&self, uuid: uuid::Uuid) -> Result<(), ComputeError> {
+        let resp = self.delete_request(&format!("jobs/{uuid}")).await;
+        match resp {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to delete job {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Start a new pool
+    ///
+    /// # Arguments
+    /// * `self` - The [`ComputeClient`]
+    /// * `pool` - the pool to create as [`PoolCreationInput`]
+    pub async fn post_pool(
+        &self,
+        pool: models::PoolCreationInput,
+    ) -> Result<models::Id, ComputeError> {
+        let resp = self.post_request("pools", Some(pool), None).await?;
+        resp.json::<models::Id>().await
+    }
+
+    /// Get all user pools
+    ///
+    /// # Arguments
+    /// * `self` - The [`ComputeClient`]
+    /// * `tags` - A slice of [`&str`] for filtering pools by tags
+    pub async fn get_pools(
+        &self,
+        tags: Option<&[&str]>,
+    ) -> Result<Vec<models::PoolOutput>, ComputeError> {
+        let mut query: Option<Vec<(&str, &str)>> = None;
+        if let Some(tags) = tags {
+            query = Some(
+                tags.iter()
+                    .map(|e| ("tag", *e))
+                    .collect::<Vec<(&str, &str)>>(),
+            );
+        }
+        let resp = self.get_request("pools", query).await?;
+        resp.json::<Vec<models::PoolOutput>>().await
+    }
+
+    /// Get information of the specified pool
+    ///
+    /// # Arguments
+    /// * `self` - The [`ComputeClient`]
+    /// * `uuid` - Uuid of the pool
+    pub async fn get_pool_info(
+        &self,
+        uuid: uuid::Uuid,
+    ) -> Result<models::PoolOutput, ComputeError> {
+        let resp = self.get_request(&format!("pools/{uuid}"), None).await?;
+        resp.json::<models::PoolOutput>().await
+    }
+
+    /// Update a running pool
+    ///
+    /// # Arguments
+    /// * `self` - the [`ComputeClient`]
+    /// * `uuid` - Uuid of the pool to update
+    /// * `pool` - the [`PoolUpdateInput`]
+    pub async fn put_update_pool(
+        &self,
+        uuid: uuid::Uuid,
+        pool: models::PoolUpdateInput,
+    ) -> Result<(), ComputeError> {
+        let resp = self
+            .put_request(&format!("pools/{uuid}"), Some(pool), None)
+            .await;
+        match resp {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to update pool {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Trigger resource update of running pool on compute nodes
+    ///
+    /// Same behaviour as [`ComputeClient::patch_update_task_resources`]:
+    /// files deleted from the bucket WILL NOT be deleted from compute nodes.
+    ///
+    /// # Arguments
+    /// * `self` - the [`ComputeClient`]
+    /// * `uuid` - Uuid of the pool
+    pub async fn patch_update_pool_resources(&self, uuid: uuid::Uuid) -> Result<(), ComputeError> {
+        let resp = self
+            .patch_request::<()>(&format!("pools/{uuid}"), None)
+            .await;
+        match resp {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to update pool resources {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Close a pool, releasing its nodes
+    ///
+    /// # Arguments
+    /// * `self` - the [`ComputeClient`]
+    /// * `uuid` - Uuid of the pool to close
+    pub async fn post_close_pool(&self, uuid: uuid::Uuid) -> Result<(), ComputeError> {
+        let resp = self
+            .safe_post_request::<()>(&format!("pools/{uuid}/close"), Some(()), None)
+            .await;
+        match resp {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to close pool {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Delete a pool (close if needed)
+    ///
+    /// # Arguments
+    /// * `self` - the [`ComputeClient`]
+    /// * `uuid` - Uuid of the pool to delete
+    pub async fn delete_pool(&self, uuid: uuid::Uuid) -> Result<(), ComputeError> {
+        let resp = self.delete_request(&format!("pools/{uuid}")).await;
+        match resp {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to delete pool {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Get the value of a secret
+    ///
+    /// # Arguments
+    /// * `self` - the [`ComputeClient`]
+    /// * `key` - Full path of the secret
+    pub async fn get_secret(&self, key: &str) -> Result<String, ComputeError> {
+        let resp = self.get_request(&secret_route("data", key), None).await;
+        match resp {
+            Ok(resp) => Ok(resp.json::<models::SecretValue>().await?.value),
+            Err(e) => {
+                error!("Failed to get secret {} {:?}", key, e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Create a secret
+    ///
+    /// # Arguments
+    /// * `self` - the [`ComputeClient`]
+    /// * `key` - Full path of the secret
+    /// * `value` - Value of the secret
+    pub async fn put_secret(&self, key: &str, value: &str) -> Result<(), ComputeError> {
+        let secret = models::SecretValue {
+            value: value.to_owned(),
+        };
+        let resp = self
+            .put_request(&secret_route("data", key), Some(secret), None)
+            .await;
+        match resp {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to create secret {} {:?}", key, e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Update the value of a secret
+    ///
+    /// # Arguments
+    /// * `self` - the [`ComputeClient`]
+    /// * `key` - Full path of the secret
+    /// * `value` - New value of the secret
+    pub async fn patch_secret(&self, key: &str, value: &str) -> Result<(), ComputeError> {
+        let secret = models::SecretValue {
+            value: value.to_owned(),
+        };
+        let resp = self
+            .patch_request(&secret_route("data", key), Some(secret))
+            .await;
+        match resp {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to update secret {} {:?}", key, e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Delete a secret
+    ///
+    /// # Arguments
+    /// * `self` - the [`ComputeClient`]
+    /// * `key` - Full path of the secret
+    pub async fn delete_secret(&self, key: &str) -> Result<(), ComputeError> {
+        let resp = self.delete_request(&secret_route("data", key)).await;
+        match resp {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to delete secret {} {:?}", key, e);
+                Err(e)
+            }
+        }
+    }
+
+    /// List the secrets starting with a prefix
+    ///
+    /// # Arguments
+    /// * `self` - the [`ComputeClient`]
+    /// * `prefix` - Prefix of the secrets, empty for every secret
+    /// * `recursive` - Whether to list the secrets of the sub-directories of
+    ///   `prefix`, instead of the sub-directories themselves (ending with `/`)
+    pub async fn get_secrets(
+        &self,
+        prefix: &str,
+        recursive: bool,
+    ) -> Result<Vec<String>, ComputeError> {
+        let query = vec![("recursive", if recursive { "true" } else { "false" })];
+        let resp = self
+            .get_request(&secret_route("search", prefix), Some(query))
+            .await;
+        match resp {
+            Ok(resp) => resp.json::<Vec<String>>().await,
+            Err(e) => {
+                error!("Failed to list secrets under {} {:?}", prefix, e);
+                Err(e)
+            }
+        }
+    }
+
     /// Get task standard output (can be rotated)
     ///
     /// # Arguments
@@ -539,7 +1124,7 @@ impl ComputeClient {
         let resp = self
             .get_request(&format!("tasks/{uuid}/stdout"), None)
             .await?;
-        resp.text().await.map_err(|_| ComputeError::Generic)
+        resp.text().await
     }
 
     /// Get task standard error (can be rotated)
@@ -551,7 +1136,7 @@ impl ComputeClient {
         let resp = self
             .get_request(&format!("tasks/{uuid}/stderr"), None)
             .await?;
-        resp.text().await.map_err(|_| ComputeError::Generic)
+        resp.text().await
     }
 
     /// Get task last standard output (can be rotated)
@@ -561,9 +1146,9 @@ impl ComputeClient {
     /// * `uuid` - Uuid of the task
     pub async fn post_task_last_stdout(&self, uuid: uuid::Uuid) -> Result<String, ComputeError> {
         let resp = self
-            .post_request::<()>(&format!("tasks/{uuid}/stdout"), None, None)
+            .safe_post_request::<()>(&format!("tasks/{uuid}/stdout"), None, None)
             .await?;
-        resp.text().await.map_err(|_| ComputeError::Generic)
+        resp.text().await
     }
 
     /// Get task last standard error (can be rotated)
@@ -573,9 +1158,9 @@ impl ComputeClient {
     /// * `uuid` - Uuid of the task
     pub async fn post_task_last_stderr(&self, uuid: uuid::Uuid) -> Result<String, ComputeError> {
         let resp = self
-            .post_request::<()>(&format!("tasks/{uuid}/stderr"), None, None)
+            .safe_post_request::<()>(&format!("tasks/{uuid}/stderr"), None, None)
             .await?;
-        resp.text().await.map_err(|_| ComputeError::Generic)
+        resp.text().await
     }
 
     /// Get instance standard output (can be rotated)
@@ -592,7 +1177,7 @@ impl ComputeClient {
         let resp = self
             .get_request(&format!("tasks/{uuid}/stdout/{instance_id}"), None)
             .await?;
-        resp.text().await.map_err(|_| ComputeError::Generic)
+        resp.text().await
     }
 
     /// Get instance standard error (can be rotated)
@@ -608,7 +1193,7 @@ impl ComputeClient {
         let resp = self
             .get_request(&format!("tasks/{uuid}/stderr/{instance_id}"), None)
             .await?;
-        resp.text().await.map_err(|_| ComputeError::Generic)
+        resp.text().await
     }
 
     /// Get instance last standard output (can be rotated)
@@ -622,9 +1207,9 @@ impl ComputeClient {
         instance_id: u32,
     ) -> Result<String, ComputeError> {
         let resp = self
-            .post_request::<()>(&format!("tasks/{uuid}/stdout/{instance_id}"), None, None)
+            .safe_post_request::<()>(&format!("tasks/{uuid}/stdout/{instance_id}"), None, None)
             .await?;
-        resp.text().await.map_err(|_| ComputeError::Generic)
+        resp.text().await
     }
 
     /// Get instance last standard error (can be rotated)
@@ -638,8 +1223,114 @@ impl ComputeClient {
         instance_id: u32,
     ) -> Result<String, ComputeError> {
         let resp = self
-            .post_request::<()>(&format!("tasks/{uuid}/stderr/{instance_id}"), None, None)
+            .safe_post_request::<()>(&format!("tasks/{uuid}/stderr/{instance_id}"), None, None)
             .await?;
-        resp.text().await.map_err(|_| ComputeError::Generic)
+        resp.text().await
+    }
+}
+
+/// Route of a secret or secrets prefix, each `/` separated segment of `path`
+/// is percent-encoded
+fn secret_route(kind: &str, path: &str) -> String {
+    let segments: Vec<String> = path
+        .split('/')
+        .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT).to_string())
+        .collect();
+    format!("secrets-manager/{kind}/{}", segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, body: &str) -> Response {
+        Response::from(
+            http::Response::builder()
+                .status(status)
+                .body(body.to_owned())
+                .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn success_statuses() {
+        let created = r#"{"uuid": "52c10b2d-0687-41e1-985e-7279f6dd543a"}"#;
+        let resp = ComputeClient::check_status(response(201, created), Method::POST, "tasks").await;
+        let id = resp.unwrap().json::<models::Id>().await;
+        assert!(id.is_ok_and(|id| id.uuid.is_some()));
+
+        for status in [200, 202, 204] {
+            let resp =
+                ComputeClient::check_status(response(status, ""), Method::DELETE, "tasks").await;
+            assert!(resp.is_ok(), "status {status} should be a success");
+        }
+    }
+
+    #[tokio::test]
+    async fn error_statuses() {
+        let body =
+            r#"{"code": "INVALID_SHORTNAME", "message": "Invalid shortname", "debug": null}"#;
+        let resp = ComputeClient::check_status(response(400, body), Method::POST, "tasks").await;
+        match resp {
+            Err(ComputeError::Validation(e)) => {
+                assert_eq!(e.status, StatusCode::BAD_REQUEST);
+                assert_eq!(e.method, Method::POST);
+                assert_eq!(e.route, "tasks");
+                let code = e.error.as_ref().and_then(|q| q.code.as_deref());
+                assert_eq!(code, Some("INVALID_SHORTNAME"));
+            }
+            _ => panic!("400 should be a validation error"),
+        }
+
+        let resp = ComputeClient::check_status(response(409, ""), Method::POST, "tasks").await;
+        assert!(matches!(resp, Err(ComputeError::Conflict(_))));
+        let resp = ComputeClient::check_status(response(429, ""), Method::GET, "tasks").await;
+        assert!(matches!(resp, Err(ComputeError::RateLimited(_))));
+        let resp = ComputeClient::check_status(response(502, "Bad Gateway"), Method::GET, "").await;
+        match resp {
+            Err(ComputeError::Api(e)) => {
+                assert!(e.error.is_none());
+                assert_eq!(e.body, "Bad Gateway");
+            }
+            _ => panic!("502 should be a generic API error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn rate_limit_retry_after() {
+        let resp = Response::from(
+            http::Response::builder()
+                .status(429)
+                .header("Retry-After", "7")
+                .body(String::new())
+                .unwrap(),
+        );
+        let resp = ComputeClient::check_status(resp, Method::GET, "tasks").await;
+        let Err(error) = resp else {
+            panic!("429 should be an error");
+        };
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry(&error, false));
+        assert_eq!(policy.delay(1, &error), std::time::Duration::from_secs(7));
+    }
+
+    #[tokio::test]
+    async fn deserialize_error() {
+        let resp = ComputeClient::check_status(response(200, "[]"), Method::GET, "info").await;
+        let info = resp.unwrap().json::<UserInfo>().await;
+        assert!(matches!(info, Err(ComputeError::Deserialize { body, .. }) if body == "[]"));
+    }
+
+    #[test]
+    fn secret_routes_are_encoded() {
+        assert_eq!(
+            secret_route("data", "app/db password?"),
+            "secrets-manager/data/app/db%20password%3F"
+        );
+        assert_eq!(
+            secret_route("search", "app/100%/"),
+            "secrets-manager/search/app/100%25/"
+        );
+        assert_eq!(secret_route("search", ""), "secrets-manager/search/");
     }
 }
@@ -0,0 +1,643 @@
+use crate::compute::client::ComputeClient;
+use crate::compute::models::hardware_constraint::{
+    CpuModel, Gpu, MaximumCore, MaximumRam, MaximumRamCoreRatio, MinimumCore, MinimumRam,
+    MinimumRamCoreRatio, NoGpu, NoSsd, Specific, Ssd,
+};
+use crate::compute::models::HardwareConstraintVariant;
+use crate::compute::ComputeError;
+
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+
+/// Megabytes in a gigabyte, the API expects memory sizes in MB
+const MB_PER_GB: f64 = 1024.0;
+
+/// Hardware constraint of a task or pool, serialized with its discriminator
+///
+/// ```
+/// use qarnot::compute::constraints::{min_ram_gb, Constraint};
+///
+/// let constraints = Constraint::min_cores(16).and(min_ram_gb(64)).ssd().no_gpu();
+/// assert!(constraints.validate().is_ok());
+/// ```
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "discriminator")]
+pub enum Constraint {
+    /// At least `core_count` cores
+    #[serde(rename = "MinimumCoreHardwareConstraint", rename_all = "camelCase")]
+    MinCores { core_count: i32 },
+    /// At most `core_count` cores
+    #[serde(rename = "MaximumCoreHardwareConstraint", rename_all = "camelCase")]
+    MaxCores { core_count: i32 },
+    /// At least `minimum_memory_mb` MB of memory
+    #[serde(rename = "MinimumRamHardwareConstraint")]
+    MinRam {
+        #[serde(rename = "minimumMemoryMB", alias = "minimumMemoryMb")]
+        minimum_memory_mb: f64,
+    },
+    /// At most `maximum_memory_mb` MB of memory
+    #[serde(rename = "MaximumRamHardwareConstraint")]
+    MaxRam {
+        #[serde(rename = "maximumMemoryMB", alias = "maximumMemoryMb")]
+        maximum_memory_mb: f64,
+    },
+    /// At least `minimum_memory_gb_core_ratio` GB of memory per core
+    #[serde(rename = "MinimumRamCoreRatioHardwareConstraint")]
+    MinRamCoreRatio {
+        #[serde(
+            rename = "minimumMemoryGBCoreRatio",
+            alias = "minimumMemoryGbCoreRatio"
+        )]
+        minimum_memory_gb_core_ratio: f64,
+    },
+    /// At most `maximum_memory_gb_core_ratio` GB of memory per core
+    #[serde(rename = "MaximumRamCoreRatioHardwareConstraint")]
+    MaxRamCoreRatio {
+        #[serde(
+            rename = "maximumMemoryGBCoreRatio",
+            alias = "maximumMemoryGbCoreRatio"
+        )]
+        maximum_memory_gb_core_ratio: f64,
+    },
+    /// Hardware whose name contains the key
+    #[serde(rename = "SpecificHardwareConstraint", rename_all = "camelCase")]
+    Specific { specification_key: String },
+    /// Hardware with the CPU model `cpu_model`
+    #[serde(rename = "CpuModelHardwareConstraint", rename_all = "camelCase")]
+    CpuModel { cpu_model: String },
+    /// Hardware with an SSD
+    #[serde(rename = "SSDHardwareConstraint")]
+    Ssd,
+    /// Hardware without SSD
+    #[serde(rename = "NoSSDHardwareConstraint")]
+    NoSsd,
+    /// Hardware with a GPU
+    #[serde(rename = "GpuHardwareConstraint")]
+    Gpu,
+    /// Hardware without GPU
+    #[serde(rename = "NoGpuHardwareConstraint")]
+    NoGpu,
+}
+
+/// At least `count` cores
+pub const fn min_cores(count: i32) -> Constraint {
+    Constraint::MinCores { core_count: count }
+}
+
+/// At most `count` cores
+pub const fn max_cores(count: i32) -> Constraint {
+    Constraint::MaxCores { core_count: count }
+}
+
+/// At least `gb` GB of memory
+pub fn min_ram_gb(gb: impl Into<f64>) -> Constraint {
+    Constraint::MinRam {
+        minimum_memory_mb: gb.into() * MB_PER_GB,
+    }
+}
+
+/// At most `gb` GB of memory
+pub fn max_ram_gb(gb: impl Into<f64>) -> Constraint {
+    Constraint::MaxRam {
+        maximum_memory_mb: gb.into() * MB_PER_GB,
+    }
+}
+
+/// At least `gb` GB of memory per core
+pub const fn min_ram_gb_per_core(gb: f64) -> Constraint {
+    Constraint::MinRamCoreRatio {
+        minimum_memory_gb_core_ratio: gb,
+    }
+}
+
+/// At most `gb` GB of memory per core
+pub const fn max_ram_gb_per_core(gb: f64) -> Constraint {
+    Constraint::MaxRamCoreRatio {
+        maximum_memory_gb_core_ratio: gb,
+    }
+}
+
+/// Hardware whose name contains `key`
+pub fn specific(key: &str) -> Constraint {
+    Constraint::Specific {
+        specification_key: key.to_owned(),
+    }
+}
+
+/// Hardware with the CPU model `model`
+pub fn cpu_model(model: &str) -> Constraint {
+    Constraint::CpuModel {
+        cpu_model: model.to_owned(),
+    }
+}
+
+impl Constraint {
+    /// At least `count` cores
+    pub const fn min_cores(count: i32) -> Self {
+        min_cores(count)
+    }
+
+    /// At most `count` cores
+    pub const fn max_cores(count: i32) -> Self {
+        max_cores(count)
+    }
+
+    /// At least `gb` GB of memory
+    pub fn min_ram_gb(gb: impl Into<f64>) -> Self {
+        min_ram_gb(gb)
+    }
+
+    /// At most `gb` GB of memory
+    pub fn max_ram_gb(gb: impl Into<f64>) -> Self {
+        max_ram_gb(gb)
+    }
+
+    /// At least `gb` GB of memory per core
+    pub const fn min_ram_gb_per_core(gb: f64) -> Self {
+        min_ram_gb_per_core(gb)
+    }
+
+    /// At most `gb` GB of memory per core
+    pub const fn max_ram_gb_per_core(gb: f64) -> Self {
+        max_ram_gb_per_core(gb)
+    }
+
+    /// Hardware whose name contains `key`
+    pub fn specific(key: &str) -> Self {
+        specific(key)
+    }
+
+    /// Hardware with the CPU model `model`
+    pub fn cpu_model(model: &str) -> Self {
+        cpu_model(model)
+    }
+
+    /// Set of this constraint and `other`
+    #[must_use]
+    pub fn and(self, other: Self) -> HardwareConstraints {
+        HardwareConstraints::from(self).and(other)
+    }
+
+    /// Set of this constraint and an SSD
+    #[must_use]
+    pub fn ssd(self) -> HardwareConstraints {
+        self.and(Self::Ssd)
+    }
+
+    /// Set of this constraint and no SSD
+    #[must_use]
+    pub fn no_ssd(self) -> HardwareConstraints {
+        self.and(Self::NoSsd)
+    }
+
+    /// Set of this constraint and a GPU
+    #[must_use]
+    pub fn gpu(self) -> HardwareConstraints {
+        self.and(Self::Gpu)
+    }
+
+    /// Set of this constraint and no GPU
+    #[must_use]
+    pub fn no_gpu(self) -> HardwareConstraints {
+        self.and(Self::NoGpu)
+    }
+
+    /// Discriminator of the constraint in the API
+    pub const fn discriminator(&self) -> &'static str {
+        match self {
+            Self::MinCores { .. } => "MinimumCoreHardwareConstraint",
+            Self::MaxCores { .. } => "MaximumCoreHardwareConstraint",
+            Self::MinRam { .. } => "MinimumRamHardwareConstraint",
+            Self::MaxRam { .. } => "MaximumRamHardwareConstraint",
+            Self::MinRamCoreRatio { .. } => "MinimumRamCoreRatioHardwareConstraint",
+            Self::MaxRamCoreRatio { .. } => "MaximumRamCoreRatioHardwareConstraint",
+            Self::Specific { .. } => "SpecificHardwareConstraint",
+            Self::CpuModel { .. } => "CpuModelHardwareConstraint",
+            Self::Ssd => "SSDHardwareConstraint",
+            Self::NoSsd => "NoSSDHardwareConstraint",
+            Self::Gpu => "GpuHardwareConstraint",
+            Self::NoGpu => "NoGpuHardwareConstraint",
+        }
+    }
+
+    /// Whether the constraint can be met by one of the `catalog` constraints
+    ///
+    /// A minimum is met by a catalog constraint of the same type with a
+    /// greater or equal value, a maximum by a lower or equal value, and a
+    /// specific hardware or CPU model by a catalog key containing it.
+    pub fn is_offered_by(&self, catalog: &[Self]) -> bool {
+        catalog.iter().any(|offer| match (self, offer) {
+            (Self::MinCores { core_count: c }, Self::MinCores { core_count: o }) => c <= o,
+            (Self::MaxCores { core_count: c }, Self::MaxCores { core_count: o }) => c >= o,
+            (
+                Self::MinRam {
+                    minimum_memory_mb: c,
+                },
+                Self::MinRam {
+                    minimum_memory_mb: o,
+                },
+            ) => c <= o,
+            (
+                Self::MaxRam {
+                    maximum_memory_mb: c,
+                },
+                Self::MaxRam {
+                    maximum_memory_mb: o,
+                },
+            ) => c >= o,
+            (
+                Self::MinRamCoreRatio {
+                    minimum_memory_gb_core_ratio: c,
+                },
+                Self::MinRamCoreRatio {
+                    minimum_memory_gb_core_ratio: o,
+                },
+            ) => c <= o,
+            (
+                Self::MaxRamCoreRatio {
+                    maximum_memory_gb_core_ratio: c,
+                },
+                Self::MaxRamCoreRatio {
+                    maximum_memory_gb_core_ratio: o,
+                },
+            ) => c >= o,
+            (
+                Self::Specific {
+                    specification_key: c,
+                },
+                Self::Specific {
+                    specification_key: o,
+                },
+            ) => o.contains(c.as_str()),
+            (Self::CpuModel { cpu_model: c }, Self::CpuModel { cpu_model: o }) => {
+                o.contains(c.as_str())
+            }
+            (constraint, offer) => constraint == offer,
+        })
+    }
+}
+
+impl From<Constraint> for HardwareConstraintVariant {
+    fn from(constraint: Constraint) -> Self {
+        let discriminator = Some(constraint.discriminator().to_owned());
+        match constraint {
+            Constraint::MinCores { core_count } => {
+                Self::MinimumCoreHardware(Box::new(MinimumCore {
+                    discriminator,
+                    core_count: Some(core_count),
+                }))
+            }
+            Constraint::MaxCores { core_count } => {
+                Self::MaximumCoreHardware(Box::new(MaximumCore {
+                    discriminator,
+                    core_count: Some(core_count),
+                }))
+            }
+            Constraint::MinRam { minimum_memory_mb } => {
+                Self::MinimumRamHardware(Box::new(MinimumRam {
+                    discriminator,
+                    minimum_memory_mb: Some(minimum_memory_mb),
+                }))
+            }
+            Constraint::MaxRam { maximum_memory_mb } => {
+                Self::MaximumRamHardware(Box::new(MaximumRam {
+                    discriminator,
+                    maximum_memory_mb: Some(maximum_memory_mb),
+                }))
+            }
+            Constraint::MinRamCoreRatio {
+                minimum_memory_gb_core_ratio,
+            } => Self::MinimumRamCoreRatioHardware(Box::new(MinimumRamCoreRatio {
+                discriminator,
+                minimum_memory_gb_core_ratio: Some(minimum_memory_gb_core_ratio),
+            })),
+            Constraint::MaxRamCoreRatio {
+                maximum_memory_gb_core_ratio,
+            } => Self::MaximumRamCoreRatioHardware(Box::new(MaximumRamCoreRatio {
+                discriminator,
+                maximum_memory_gb_core_ratio: Some(maximum_memory_gb_core_ratio),
+            })),
+            Constraint::Specific { specification_key } => {
+                Self::SpecificHardware(Box::new(Specific {
+                    discriminator,
+                    specification_key: Some(specification_key),
+                }))
+            }
+            Constraint::CpuModel { cpu_model } => Self::CpuModelHardware(Box::new(CpuModel {
+                discriminator,
+                cpu_model: Some(cpu_model),
+            })),
+            Constraint::Ssd => Self::SsdHardware(Box::new(Ssd { discriminator })),
+            Constraint::NoSsd => Self::NoSsdHardware(Box::new(NoSsd { discriminator })),
+            Constraint::Gpu => Self::GpuHardware(Box::new(Gpu { discriminator })),
+            Constraint::NoGpu => Self::NoGpuHardware(Box::new(NoGpu { discriminator })),
+        }
+    }
+}
+
+/// Known discriminators are read through [`Constraint`], the others are kept
+/// as is in `Unknown`
+impl<'de> Deserialize<'de> for HardwareConstraintVariant {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        Ok(match Constraint::deserialize(&value) {
+            Ok(constraint) => constraint.into(),
+            Err(_) => Self::Unknown(value),
+        })
+    }
+}
+
+impl TryFrom<&HardwareConstraintVariant> for Constraint {
+    type Error = serde_json::Error;
+
+    /// Fails for unknown constraints and constraints missing their value
+    fn try_from(variant: &HardwareConstraintVariant) -> Result<Self, Self::Error> {
+        Self::deserialize(serde_json::to_value(variant)?)
+    }
+}
+
+/// Contradiction between hardware constraints, no hardware can meet them
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConstraintConflict {
+    /// The minimum number of cores is above the maximum
+    Cores { min: i32, max: i32 },
+    /// The minimum memory is above the maximum, in MB
+    Ram { min_mb: f64, max_mb: f64 },
+    /// The minimum memory per core is above the maximum, in GB
+    RamCoreRatio { min_gb: f64, max_gb: f64 },
+    /// Both an SSD and no SSD are required
+    SsdAndNoSsd,
+    /// Both a GPU and no GPU are required
+    GpuAndNoGpu,
+}
+
+impl fmt::Display for ConstraintConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Cores { min, max } => {
+                write!(f, "at least {min} cores and at most {max} cores requested")
+            }
+            Self::Ram { min_mb, max_mb } => write!(
+                f,
+                "at least {min_mb} MB and at most {max_mb} MB of memory requested"
+            ),
+            Self::RamCoreRatio { min_gb, max_gb } => write!(
+                f,
+                "at least {min_gb} GB and at most {max_gb} GB of memory per core requested"
+            ),
+            Self::SsdAndNoSsd => write!(f, "both SSD and no SSD requested"),
+            Self::GpuAndNoGpu => write!(f, "both GPU and no GPU requested"),
+        }
+    }
+}
+
+/// Set of hardware constraints, all of them must be met
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct HardwareConstraints {
+    pub constraints: Vec<Constraint>,
+}
+
+impl From<Constraint> for HardwareConstraints {
+    fn from(constraint: Constraint) -> Self {
+        Self {
+            constraints: vec![constraint],
+        }
+    }
+}
+
+impl From<HardwareConstraints> for Vec<HardwareConstraintVariant> {
+    fn from(constraints: HardwareConstraints) -> Self {
+        constraints
+            .constraints
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+}
+
+impl FromIterator<Constraint> for HardwareConstraints {
+    fn from_iter<I: IntoIterator<Item = Constraint>>(constraints: I) -> Self {
+        Self {
+            constraints: constraints.into_iter().collect(),
+        }
+    }
+}
+
+impl HardwareConstraints {
+    /// Empty set of constraints
+    pub const fn new() -> Self {
+        Self {
+            constraints: Vec::new(),
+        }
+    }
+
+    /// Add `constraint` to the set
+    #[must_use]
+    pub fn and(mut self, constraint: Constraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    /// Add an SSD to the set
+    #[must_use]
+    pub fn ssd(self) -> Self {
+        self.and(Constraint::Ssd)
+    }
+
+    /// Add no SSD to the set
+    #[must_use]
+    pub fn no_ssd(self) -> Self {
+        self.and(Constraint::NoSsd)
+    }
+
+    /// Add a GPU to the set
+    #[must_use]
+    pub fn gpu(self) -> Self {
+        self.and(Constraint::Gpu)
+    }
+
+    /// Add no GPU to the set
+    #[must_use]
+    pub fn no_gpu(self) -> Self {
+        self.and(Constraint::NoGpu)
+    }
+
+    /// Check that some hardware can meet every constraint of the set
+    ///
+    /// # Errors
+    /// Every contradiction between the constraints, see [`ConstraintConflict`]
+    pub fn validate(&self) -> Result<(), Vec<ConstraintConflict>> {
+        let conflicts = conflicts(&self.constraints);
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts)
+        }
+    }
+
+    /// Constraints of the set that no constraint of `catalog` can meet,
+    /// see [`Constraint::is_offered_by`]
+    pub fn unavailable(&self, catalog: &[Constraint]) -> Vec<&Constraint> {
+        self.constraints
+            .iter()
+            .filter(|c| !c.is_offered_by(catalog))
+            .collect()
+    }
+
+    /// Fetch the catalog of the platform and return the constraints of the
+    /// set it cannot meet
+    ///
+    /// # Arguments
+    /// * `compute_client` - Client used to fetch the catalog
+    pub async fn unavailable_on(
+        &self,
+        compute_client: &ComputeClient,
+    ) -> Result<Vec<&Constraint>, ComputeError> {
+        let catalog: Vec<HardwareConstraintVariant> = compute_client
+            .hardware_constraints_stream(None)
+            .try_collect()
+            .await?;
+        let catalog: Vec<Constraint> = catalog
+            .iter()
+            .filter_map(|c| Constraint::try_from(c).ok())
+            .collect();
+        Ok(self.unavailable(&catalog))
+    }
+}
+
+/// Contradictions between `constraints`
+pub(crate) fn conflicts(constraints: &[Constraint]) -> Vec<ConstraintConflict> {
+    let mut conflicts = Vec::new();
+    let (mut min_cores, mut max_cores) = (None::<i32>, None::<i32>);
+    let (mut min_ram, mut max_ram) = (None::<f64>, None::<f64>);
+    let (mut min_ratio, mut max_ratio) = (None::<f64>, None::<f64>);
+    for constraint in constraints {
+        match *constraint {
+            Constraint::MinCores { core_count } => {
+                min_cores = min_cores.max(Some(core_count));
+            }
+            Constraint::MaxCores { core_count } => {
+                max_cores = Some(max_cores.map_or(core_count, |m| m.min(core_count)));
+            }
+            Constraint::MinRam { minimum_memory_mb } => {
+                min_ram = Some(min_ram.map_or(minimum_memory_mb, |m| m.max(minimum_memory_mb)));
+            }
+            Constraint::MaxRam { maximum_memory_mb } => {
+                max_ram = Some(max_ram.map_or(maximum_memory_mb, |m| m.min(maximum_memory_mb)));
+            }
+            Constraint::MinRamCoreRatio {
+                minimum_memory_gb_core_ratio: ratio,
+            } => min_ratio = Some(min_ratio.map_or(ratio, |m| m.max(ratio))),
+            Constraint::MaxRamCoreRatio {
+                maximum_memory_gb_core_ratio: ratio,
+            } => max_ratio = Some(max_ratio.map_or(ratio, |m| m.min(ratio))),
+            _ => (),
+        }
+    }
+    if let (Some(min), Some(max)) = (min_cores, max_cores) {
+        if min > max {
+            conflicts.push(ConstraintConflict::Cores { min, max });
+        }
+    }
+    if let (Some(min_mb), Some(max_mb)) = (min_ram, max_ram) {
+        if min_mb > max_mb {
+            conflicts.push(ConstraintConflict::Ram { min_mb, max_mb });
+        }
+    }
+    if let (Some(min_gb), Some(max_gb)) = (min_ratio, max_ratio) {
+        if min_gb > max_gb {
+            conflicts.push(ConstraintConflict::RamCoreRatio { min_gb, max_gb });
+        }
+    }
+    if constraints.contains(&Constraint::Ssd) && constraints.contains(&Constraint::NoSsd) {
+        conflicts.push(ConstraintConflict::SsdAndNoSsd);
+    }
+    if constraints.contains(&Constraint::Gpu) && constraints.contains(&Constraint::NoGpu) {
+        conflicts.push(ConstraintConflict::GpuAndNoGpu);
+    }
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_with_discriminator() {
+        let constraints = Constraint::min_cores(16)
+            .and(min_ram_gb(64.0))
+            .ssd()
+            .no_gpu()
+            .and(specific("16c-128g"));
+        let expected = serde_json::json!([
+            {"discriminator": "MinimumCoreHardwareConstraint", "coreCount": 16},
+            {"discriminator": "MinimumRamHardwareConstraint", "minimumMemoryMB": 65536.0},
+            {"discriminator": "SSDHardwareConstraint"},
+            {"discriminator": "NoGpuHardwareConstraint"},
+            {"discriminator": "SpecificHardwareConstraint", "specificationKey": "16c-128g"},
+        ]);
+        assert_eq!(serde_json::to_value(&constraints).unwrap(), expected);
+        let variants: Vec<HardwareConstraintVariant> = constraints.clone().into();
+        assert_eq!(serde_json::to_value(&variants).unwrap(), expected);
+        let parsed: Vec<Constraint> = variants
+            .iter()
+            .map(|v| Constraint::try_from(v).unwrap())
+            .collect();
+        assert_eq!(parsed, constraints.constraints);
+    }
+
+    #[test]
+    fn contradictions() {
+        assert!(min_cores(8).and(max_cores(8)).validate().is_ok());
+        let conflicts = min_cores(16)
+            .and(max_cores(32))
+            .and(max_cores(8))
+            .and(max_ram_gb(4))
+            .and(min_ram_gb(8))
+            .and(Constraint::min_ram_gb_per_core(4.0))
+            .and(Constraint::max_ram_gb_per_core(2.0))
+            .ssd()
+            .no_ssd()
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            conflicts,
+            vec![
+                ConstraintConflict::Cores { min: 16, max: 8 },
+                ConstraintConflict::Ram {
+                    min_mb: 8192.0,
+                    max_mb: 4096.0
+                },
+                ConstraintConflict::RamCoreRatio {
+                    min_gb: 4.0,
+                    max_gb: 2.0
+                },
+                ConstraintConflict::SsdAndNoSsd,
+            ]
+        );
+    }
+
+    #[test]
+    fn match_catalog() {
+        let catalog = vec![
+            min_cores(32),
+            min_ram_gb(128.0),
+            specific("16c-128g-nvme"),
+            Constraint::Ssd,
+        ];
+        let constraints = min_cores(16)
+            .and(min_ram_gb(256.0))
+            .and(specific("16c-128g"))
+            .ssd()
+            .gpu();
+        assert_eq!(
+            constraints.unavailable(&catalog),
+            vec![&min_ram_gb(256.0), &Constraint::Gpu]
+        );
+    }
+}
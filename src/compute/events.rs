@@ -0,0 +1,148 @@
+use crate::compute::models::qtask_status_output::QTaskExecutionPhaseOutput;
+use crate::compute::models::{CompletedFrameOutput, QError};
+use crate::compute::task::{State, Task};
+
+use std::collections::{HashMap, HashSet};
+
+/// Change of a task noticed by `Task::watch()`
+#[derive(Clone, Debug)]
+pub enum TaskEvent {
+    /// The task state changed, `previous` is `None` on the first update
+    StateChanged {
+        previous: Option<State>,
+        current: State,
+    },
+    /// The task progress changed
+    Progress(f32),
+    /// A running instance entered a new execution phase
+    InstancePhase {
+        instance_id: u32,
+        phase: QTaskExecutionPhaseOutput,
+    },
+    /// An instance completed, successfully or not
+    InstanceCompleted(CompletedFrameOutput),
+    /// The task reported a new error
+    Error(QError),
+}
+
+/// Fields of a task already reported as events
+#[derive(Default)]
+pub(crate) struct TaskSnapshot {
+    state: Option<State>,
+    progress: Option<f32>,
+    phases: HashMap<u32, QTaskExecutionPhaseOutput>,
+    completed: HashSet<Option<i32>>,
+    error_count: usize,
+}
+
+impl TaskSnapshot {
+    /// Events between the snapshot and `task`, updating the snapshot
+    pub(crate) fn update(&mut self, task: &Task<'_>) -> Vec<TaskEvent> {
+        let mut events = Vec::new();
+        if let Some(current) = task
+            .state
+            .as_ref()
+            .filter(|s| self.state.as_ref() != Some(s))
+        {
+            events.push(TaskEvent::StateChanged {
+                previous: self.state.replace(current.clone()),
+                current: current.clone(),
+            });
+        }
+        if let Some(progress) = task.progress.filter(|p| Some(*p) != self.progress) {
+            self.progress = Some(progress);
+            events.push(TaskEvent::Progress(progress));
+        }
+
+        let running = task
+            .status
+            .as_ref()
+            .and_then(|s| s.running_instances_info.as_ref())
+            .and_then(|i| i.per_running_instance_info.as_ref())
+            .into_iter()
+            .flatten()
+            .filter_map(|i| Some((i.instance_id?, i.phase?)));
+        let mut phases = HashMap::new();
+        for (instance_id, phase) in running {
+            if self.phases.get(&instance_id) != Some(&phase) {
+                events.push(TaskEvent::InstancePhase { instance_id, phase });
+            }
+            phases.insert(instance_id, phase);
+        }
+        self.phases = phases;
+
+        for instance in task.completed_instances.iter().flatten() {
+            if self.completed.insert(instance.instance_id) {
+                events.push(TaskEvent::InstanceCompleted(instance.clone()));
+            }
+        }
+        let errors = task.errors.as_deref().unwrap_or_default();
+        if let Some(new_errors) = errors.get(self.error_count..) {
+            events.extend(new_errors.iter().cloned().map(TaskEvent::Error));
+        }
+        self.error_count = errors.len();
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::test_utils::{compute_client, task};
+
+    #[test]
+    fn snapshot_reports_changes_once() {
+        let compute_client = compute_client();
+        let mut task = task(&compute_client, "task");
+        let mut snapshot = TaskSnapshot::default();
+        assert!(snapshot.update(&task).is_empty());
+
+        task.state = Some(State::FullyExecuting);
+        task.progress = Some(10.0);
+        task.status = serde_json::from_value(serde_json::json!({
+            "runningInstancesInfo": {
+                "perRunningInstanceInfo": [
+                    {"instanceId": 0, "phase": "execution"},
+                    {"instanceId": 1, "phase": "download"},
+                ],
+            },
+        }))
+        .unwrap();
+        let events = snapshot.update(&task);
+        assert_eq!(events.len(), 4);
+        assert!(matches!(
+            events[0],
+            TaskEvent::StateChanged {
+                previous: None,
+                current: State::FullyExecuting
+            }
+        ));
+        assert!(snapshot.update(&task).is_empty());
+
+        task.state = Some(State::Success);
+        task.completed_instances = serde_json::from_value(serde_json::json!([
+            {"instanceId": 0},
+            {"instanceId": 1},
+        ]))
+        .unwrap();
+        task.errors = Some(vec![QError::default()]);
+        task.status = None;
+        let events = snapshot.update(&task);
+        assert!(matches!(
+            events[0],
+            TaskEvent::StateChanged {
+                previous: Some(State::FullyExecuting),
+                current: State::Success
+            }
+        ));
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| matches!(e, TaskEvent::InstanceCompleted(_)))
+                .count(),
+            2
+        );
+        assert!(matches!(events[3], TaskEvent::Error(_)));
+        assert!(snapshot.update(&task).is_empty());
+    }
+}
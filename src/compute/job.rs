@@ -0,0 +1,300 @@
+use std::str::FromStr;
+
+use crate::compute::client::ComputeClient;
+use crate::compute::models::{JobCreationInput, JobOutput, TaskOutput};
+use crate::compute::task::{InstancesOrRange, State, Task};
+use crate::compute::ComputeError;
+use crate::storage::StorageClient;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// State of a job
+/// States unknown to this client are kept in `Unknown` instead of being
+/// mistaken for another state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JobState {
+    /// The job accepts new tasks
+    Active,
+    /// The job is being terminated, its tasks are aborted
+    Terminating,
+    /// Every task of the job is finished
+    Completed,
+    /// The job was terminated
+    Terminated,
+    /// The job is being deleted
+    Deleting,
+    Unknown(String),
+}
+
+impl From<&str> for JobState {
+    fn from(state: &str) -> Self {
+        match state {
+            "Active" => Self::Active,
+            "Terminating" => Self::Terminating,
+            "Completed" => Self::Completed,
+            "Terminated" => Self::Terminated,
+            "Deleting" => Self::Deleting,
+            _ => Self::Unknown(state.to_owned()),
+        }
+    }
+}
+
+impl JobState {
+    /// Whether the job accepts new tasks
+    pub const fn is_active(&self) -> bool {
+        matches!(self, Self::Active)
+    }
+}
+
+/// High level wrapper around everything around jobs
+/// This is returned by QarnotClient::create_job()
+/// Use `.run()` to submit the job
+/// Use `.create_task()` to create tasks attached to the job
+/// Use `.wait()` to wait for all the tasks of the job to end
+pub struct Job<'a> {
+    compute_client: &'a ComputeClient,
+    storage: Option<&'a dyn StorageClient>,
+    pub name: String,
+    pub shortname: Option<String>,
+    pub pool_uuid: Option<uuid::Uuid>,
+    pub use_dependencies: bool,
+    pub max_wall_time: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub labels: Option<std::collections::HashMap<String, String>>,
+    pub auto_delete: bool,
+    pub completion_time_to_live: Option<String>,
+    pub update_cache_time: TimeDelta,
+    pub last_cache: DateTime<Utc>,
+    pub state: Option<JobState>,
+    pub previous_state: Option<JobState>,
+    pub uuid: Option<uuid::Uuid>,
+    pub creation_date: Option<DateTime<Utc>>,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+impl<'a> Job<'a> {
+    /// Set the storage client given to the tasks of the job
+    /// This is done by QarnotClient::create_job()
+    pub fn set_storage(&mut self, storage: Option<&'a dyn StorageClient>) {
+        self.storage = storage;
+    }
+
+    pub fn new(
+        compute_client: &'a ComputeClient,
+        name: &str,
+        pool_uuid: Option<uuid::Uuid>,
+        shortname: Option<String>,
+    ) -> Self {
+        Self {
+            compute_client,
+            storage: None,
+            name: name.to_owned(),
+            shortname,
+            pool_uuid,
+            use_dependencies: false,
+            max_wall_time: None,
+            tags: None,
+            labels: None,
+            auto_delete: false,
+            completion_time_to_live: None,
+            update_cache_time: TimeDelta::seconds(5),
+            last_cache: Utc::now(),
+            state: None,
+            previous_state: None,
+            uuid: None,
+            creation_date: None,
+            last_modified: None,
+        }
+    }
+
+    /// Submit job
+    pub async fn run(&mut self) -> Result<(), ComputeError> {
+        let input = JobCreationInput {
+            name: self.name.clone(),
+            shortname: self.shortname.clone(),
+            pool_uuid: self.pool_uuid,
+            use_dependencies: Some(self.use_dependencies),
+            max_wall_time: self.max_wall_time.clone(),
+            tags: self.tags.clone(),
+            labels: self.labels.clone(),
+            auto_delete_on_completion: Some(self.auto_delete),
+            completion_time_to_live: self.completion_time_to_live.clone(),
+        };
+        let res = self.compute_client.post_job(input).await?;
+        self.uuid = res.uuid;
+        if self.shortname.is_none() {
+            self.shortname = res.uuid.map(|u| u.to_string());
+        };
+        Ok(())
+    }
+
+    /// Create a new task attached to this job
+    ///
+    /// `profile` should be `None` only if the job uses a pool, in which case
+    /// the task inherits the job's pool.
+    ///
+    /// # Errors
+    /// * `ComputeError::NotSubmitted` - The job was not submitted with `.run()`
+    pub fn create_task(
+        &self,
+        name: &str,
+        profile: Option<&str>,
+        shortname: Option<String>,
+        instance_or_range: InstancesOrRange,
+    ) -> Result<Task<'a>, ComputeError> {
+        let Some(uuid) = self.uuid else {
+            error!("No uuid, have you started the job ?");
+            return Err(ComputeError::NotSubmitted);
+        };
+        // Tasks in a job must not reference a pool, they inherit the job's one
+        let mut task = Task::blank(self.compute_client, name);
+        task.shortname = shortname;
+        task.set_instances(instance_or_range);
+        task.profile = profile.map(str::to_owned);
+        task.job_uuid = Some(uuid);
+        task.set_storage(self.storage);
+        Ok(task)
+    }
+
+    /// Update current struct with values from a JobOutput
+    fn update_fields(&mut self, updated_job: JobOutput) {
+        if let Some(name) = updated_job.name {
+            self.name = name;
+        }
+        self.shortname = updated_job.shortname;
+        self.pool_uuid = updated_job.pool_uuid;
+        if let Some(use_dependencies) = updated_job.use_dependencies {
+            self.use_dependencies = use_dependencies;
+        }
+        self.max_wall_time = updated_job.max_wall_time;
+        self.tags = updated_job.tags;
+        self.labels = updated_job.labels;
+        if let Some(auto_delete) = updated_job.auto_delete_on_completion {
+            self.auto_delete = auto_delete;
+        }
+        self.completion_time_to_live = updated_job.completion_time_to_live;
+        self.uuid = updated_job.uuid;
+        self.state = updated_job.state.map(|s| JobState::from(s.as_str()));
+        self.previous_state = updated_job
+            .previous_state
+            .map(|s| JobState::from(s.as_str()));
+        self.creation_date = updated_job
+            .creation_date
+            .map(|s| DateTime::<Utc>::from_str(s.as_str()).unwrap_or_default());
+        self.last_modified = updated_job
+            .last_modified
+            .map(|s| DateTime::<Utc>::from_str(s.as_str()).unwrap_or_default());
+    }
+
+    /// Update struct with changes from the API
+    pub async fn get_update(&mut self, force_update: bool) -> Result<(), ComputeError> {
+        if let Some(uuid) = self.uuid {
+            if force_update || Utc::now() - self.last_cache >= self.update_cache_time {
+                let res = self.compute_client.get_job_info(uuid).await?;
+                self.update_fields(res);
+                self.last_cache = Utc::now();
+            }
+        }
+        Ok(())
+    }
+
+    /// Get all the tasks attached to the job
+    pub async fn tasks(&self) -> Result<Vec<TaskOutput>, ComputeError> {
+        if let Some(uuid) = self.uuid {
+            self.compute_client.get_job_tasks(uuid).await
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Wait for all the tasks of the job to finish
+    /// Returns the final state of every task of the job
+    ///
+    /// # Errors
+    /// * `ComputeError::NotSubmitted` - The job was not submitted with `.run()`
+    pub async fn wait(&mut self) -> Result<Vec<TaskOutput>, ComputeError> {
+        if self.uuid.is_none() {
+            error!("No uuid, have you started the job ?");
+            return Err(ComputeError::NotSubmitted);
+        }
+        loop {
+            let tasks = self.tasks().await?;
+            if all_finished(&tasks) {
+                self.get_update(true).await?;
+                return Ok(tasks);
+            }
+            let delay = self.update_cache_time.to_std().unwrap_or_default();
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Terminate the job, aborting all its tasks
+    pub async fn terminate(&self) -> Result<(), ComputeError> {
+        if let Some(uuid) = self.uuid {
+            self.compute_client.post_terminate_job(uuid).await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Delete the job
+    pub async fn delete(&self) -> Result<(), ComputeError> {
+        if let Some(uuid) = self.uuid {
+            self.compute_client.delete_job(uuid).await
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Whether every task is in a terminal state (Success/Cancelled/Failure)
+fn all_finished(tasks: &[TaskOutput]) -> bool {
+    tasks
+        .iter()
+        .all(|t| t.state.as_ref().is_some_and(State::is_terminal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::test_utils::compute_client;
+
+    #[test]
+    fn unknown_states_are_preserved() {
+        assert_eq!(JobState::from("Completed"), JobState::Completed);
+        assert_eq!(
+            JobState::from("Archived"),
+            JobState::Unknown(String::from("Archived"))
+        );
+        assert!(!JobState::from("Archived").is_active());
+    }
+
+    #[test]
+    fn tasks_need_a_submitted_job() {
+        let compute_client = compute_client();
+        let mut job = Job::new(&compute_client, "job", None, None);
+        assert!(matches!(
+            job.create_task("task", Some("docker-batch"), None, 1.into()),
+            Err(ComputeError::NotSubmitted)
+        ));
+        let uuid = uuid::Uuid::new_v4();
+        job.uuid = Some(uuid);
+        let task = job
+            .create_task("task", Some("docker-batch"), None, 1.into())
+            .unwrap();
+        assert_eq!(task.job_uuid, Some(uuid));
+    }
+
+    #[test]
+    fn pending_cancellations_are_not_finished() {
+        let tasks = |states: &[&str]| -> Vec<TaskOutput> {
+            states
+                .iter()
+                .map(|state| serde_json::from_value(serde_json::json!({ "state": state })).unwrap())
+                .collect()
+        };
+        assert!(!all_finished(&tasks(&["Success", "PendingCancel"])));
+        assert!(!all_finished(&tasks(&["Failure", "PendingDelete"])));
+        assert!(all_finished(&tasks(&["Success", "Cancelled", "Failure"])));
+    }
+}
@@ -1,21 +1,188 @@
 /// Low level compute client
 pub mod client;
+/// Typed hardware constraints
+pub mod constraints;
+/// Events reported while watching a task
+pub mod events;
+/// High level job manipulation
+pub mod job;
 /// Low level compute Models
 pub mod models;
+/// High level pool manipulation
+pub mod pool;
+/// Local inputs uploaded as task resources
+pub mod resources;
+/// Retry policy of the compute client
+pub mod retry;
+/// Secrets store of the account
+pub mod secrets;
 /// High level task manipulation
 pub mod task;
+/// Fluent task construction, validated before submission
+pub mod task_builder;
+#[cfg(test)]
+mod test_utils;
 
-//TODO job/pools/hwconstraints/paginate
+use crate::compute::models::QError;
+use reqwest::{Method, StatusCode};
+use std::fmt;
+
+/// Error response returned by Qarnot's compute API
+#[derive(Debug)]
+pub struct ApiError {
+    /// HTTP status of the response
+    pub status: StatusCode,
+    /// HTTP method of the failed request
+    pub method: Method,
+    /// Route of the failed request (relative to the API version)
+    pub route: String,
+    /// Error payload of the response, if it could be parsed
+    pub error: Option<QError>,
+    /// Raw body of the response
+    pub body: String,
+    /// Delay requested by the API through the `Retry-After` header
+    pub retry_after: Option<std::time::Duration>,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} returned {}", self.method, self.route, self.status)?;
+        match &self.error {
+            Some(QError {
+                code,
+                message,
+                debug,
+            }) => {
+                if let Some(code) = code {
+                    write!(f, " [{code}]")?;
+                }
+                if let Some(message) = message {
+                    write!(f, ": {message}")?;
+                }
+                if let Some(debug) = debug {
+                    write!(f, " ({debug})")?;
+                }
+                Ok(())
+            }
+            None if !self.body.is_empty() => write!(f, ": {}", self.body),
+            None => Ok(()),
+        }
+    }
+}
 
 /// Errors that may happen when using Qarnot's compute API
 #[derive(Debug)]
 pub enum ComputeError {
+    /// 400 bad request, the request was rejected by the API validation
+    Validation(Box<ApiError>),
     /// 401 unauthorized
-    Unauthorized,
+    Unauthorized(Box<ApiError>),
     /// 403 forbidden
-    Forbidden,
+    Forbidden(Box<ApiError>),
     /// 404 not found
-    NotFound,
+    NotFound(Box<ApiError>),
+    /// 409 conflict
+    Conflict(Box<ApiError>),
+    /// 429 too many requests
+    RateLimited(Box<ApiError>),
+    /// Any other error status returned by the API
+    Api(Box<ApiError>),
+    /// The request could not be sent or its response could not be read
+    Transport {
+        method: Method,
+        route: String,
+        source: reqwest::Error,
+    },
+    /// The response body does not match the expected model
+    Deserialize {
+        method: Method,
+        route: String,
+        body: String,
+        source: serde_json::Error,
+    },
+    /// The API key cannot be used as an HTTP header
+    InvalidApiKey,
+    /// The HTTP client could not be built
+    Client(reqwest::Error),
+    /// The task, job or pool was not submitted yet, it has no uuid
+    NotSubmitted,
+    /// The pool was closed or failed before opening, with its errors
+    PoolClosed(Vec<QError>),
+    /// The API answered the route without the uuid of the created resource
+    MissingUuid(String),
     /// Other kind of error
     Generic,
 }
+
+impl ComputeError {
+    /// Details of the error response, if the API answered
+    pub const fn api_error(&self) -> Option<&ApiError> {
+        match self {
+            Self::Validation(e)
+            | Self::Unauthorized(e)
+            | Self::Forbidden(e)
+            | Self::NotFound(e)
+            | Self::Conflict(e)
+            | Self::RateLimited(e)
+            | Self::Api(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// HTTP status of the error response, if the API answered
+    pub fn status(&self) -> Option<StatusCode> {
+        self.api_error().map(|e| e.status)
+    }
+
+    /// Error payload of the response, if the API answered with one
+    pub fn qerror(&self) -> Option<&QError> {
+        self.api_error().and_then(|e| e.error.as_ref())
+    }
+}
+
+impl fmt::Display for ComputeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Validation(e) => write!(f, "invalid request: {e}"),
+            Self::Unauthorized(e) => write!(f, "unauthorized: {e}"),
+            Self::Forbidden(e) => write!(f, "forbidden: {e}"),
+            Self::NotFound(e) => write!(f, "not found: {e}"),
+            Self::Conflict(e) => write!(f, "conflict: {e}"),
+            Self::RateLimited(e) => write!(f, "rate limited: {e}"),
+            Self::Api(e) => write!(f, "API error: {e}"),
+            Self::Transport {
+                method,
+                route,
+                source,
+            } => write!(f, "{method} {route} failed: {source}"),
+            Self::Deserialize {
+                method,
+                route,
+                source,
+                ..
+            } => write!(f, "{method} {route} returned an unexpected body: {source}"),
+            Self::InvalidApiKey => write!(f, "invalid API key"),
+            Self::Client(e) => write!(f, "could not build HTTP client: {e}"),
+            Self::NotSubmitted => write!(f, "not submitted yet, call run() first"),
+            Self::PoolClosed(errors) => {
+                write!(f, "pool closed before opening")?;
+                for error in errors.iter().filter_map(|e| e.message.as_deref()) {
+                    write!(f, ": {error}")?;
+                }
+                Ok(())
+            }
+            Self::MissingUuid(route) => write!(f, "{route} returned no uuid"),
+            Self::Generic => write!(f, "compute error"),
+        }
+    }
+}
+
+impl std::error::Error for ComputeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport { source, .. } | Self::Client(source) => Some(source),
+            Self::Deserialize { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -2,7 +2,7 @@ use crate::compute::models;
 use serde::{Deserialize, Serialize};
 
 /// `CompletedFrameOutput` : Information about the completed instance
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CompletedFrameOutput {
     /// List of the instance results
@@ -15,9 +15,10 @@ pub struct ForcedConstant {
 
 /// ForcedConstantAccess : Possible values for the Access property of a  ForcedConstant object.
 /// Possible values for the Access property of a  ForcedConstant object.
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum ForcedConstantAccess {
+    #[default]
     ReadOnly,
     ReadWrite,
 }
@@ -31,12 +32,6 @@ impl std::fmt::Display for ForcedConstantAccess {
     }
 }
 
-impl Default for ForcedConstantAccess {
-    fn default() -> Self {
-        Self::ReadOnly
-    }
-}
-
 impl ForcedConstant {
     /// Describe a constant to be overriden when running the task.  <br />This is meant to be used for development only and require  specific permissions.
     pub const fn new() -> Self {
@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+/// HardwareConstraintVariant : Hardware constraint of any type
+/// Constraints are deserialized according to their discriminator, unknown
+/// ones are kept as is in `Unknown`. See
+/// [`Constraint`](crate::compute::constraints::Constraint) for a typed version.
+#[derive(Clone, Debug, Serialize)]
 #[serde(untagged)]
 pub enum HardwareConstraintVariant {
     HardwareConstraint(Box<HardwareConstraint>),
@@ -16,6 +20,7 @@ pub enum HardwareConstraintVariant {
     NoGpuHardware(Box<NoGpu>),
     GpuHardware(Box<Gpu>),
     CpuModelHardware(Box<CpuModel>),
+    Unknown(serde_json::Value),
 }
 
 /// HardwareConstraint : Base hardware constraint
@@ -57,6 +62,25 @@ pub struct HardwareConstraintResponse {
     pub total: Option<u32>,
 }
 
+impl HardwareConstraintResponse {
+    /// Offset of the next page, `None` on the last page
+    ///
+    /// Without a `total` in the response, a full page means there may be more.
+    ///
+    /// # Arguments
+    /// * `offset` - Offset of the current page, used if the response has none
+    /// * `limit` - Requested page size, used if the response has none
+    pub fn next_offset(&self, offset: u32, limit: u32) -> Option<u32> {
+        let count = u32::try_from(self.data.as_ref().map_or(0, |d| d.len())).ok()?;
+        let next = self.offset.unwrap_or(offset) + count;
+        let more = match self.total {
+            Some(total) => next < total,
+            None => count == self.limit.unwrap_or(limit),
+        };
+        (count > 0 && more).then_some(next)
+    }
+}
+
 /// CpuModel : Constraint for CPU model
 #[derive(Clone, Default, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -94,6 +118,10 @@ pub struct MaximumRamCoreRatio {
     /// Type of hardware constraint
     pub discriminator: Option<String>,
     /// Maximum memory per core ratio required (in GB)
+    #[serde(
+        rename = "maximumMemoryGBCoreRatio",
+        alias = "maximumMemoryGbCoreRatio"
+    )]
     pub maximum_memory_gb_core_ratio: Option<f64>,
 }
 
@@ -103,7 +131,8 @@ pub struct MaximumRamCoreRatio {
 pub struct MaximumRam {
     /// Type of hardware constraint
     pub discriminator: Option<String>,
-    /// Maximum memory size required (in GB)
+    /// Maximum memory size required (in MB)
+    #[serde(rename = "maximumMemoryMB", alias = "maximumMemoryMb")]
     pub maximum_memory_mb: Option<f64>,
 }
 
@@ -124,6 +153,10 @@ pub struct MinimumRamCoreRatio {
     /// Type of hardware constraint
     pub discriminator: Option<String>,
     /// Minimum memory per core ratio required (in GB)
+    #[serde(
+        rename = "minimumMemoryGBCoreRatio",
+        alias = "minimumMemoryGbCoreRatio"
+    )]
     pub minimum_memory_gb_core_ratio: Option<f64>,
 }
 
@@ -133,7 +166,8 @@ pub struct MinimumRamCoreRatio {
 pub struct MinimumRam {
     /// Type of hardware constraint
     pub discriminator: Option<String>,
-    /// Minimum memory size required (in GB)
+    /// Minimum memory size required (in MB)
+    #[serde(rename = "minimumMemoryMB", alias = "minimumMemoryMb")]
     pub minimum_memory_mb: Option<f64>,
 }
 
@@ -182,3 +216,72 @@ impl Default for HardwareConstraint {
         Self::CpuModel {}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paginated_response() {
+        let page: HardwareConstraintResponse = serde_json::from_str(
+            r#"{
+              "data": [
+                {"discriminator": "MinimumCoreHardwareConstraint", "coreCount": 16},
+                {"discriminator": "NoGpuHardwareConstraint"}
+              ],
+              "offset": 0,
+              "limit": 2,
+              "total": 3
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(page.next_offset(0, 2), Some(2));
+
+        let last: HardwareConstraintResponse = serde_json::from_str(
+            r#"{"data": [{"discriminator": "SSDHardwareConstraint"}], "offset": 2, "limit": 2, "total": 3}"#,
+        )
+        .unwrap();
+        assert_eq!(last.next_offset(2, 2), None);
+    }
+
+    #[test]
+    fn paginated_response_without_total() {
+        let full: HardwareConstraintResponse = serde_json::from_str(
+            r#"{"data": [{"discriminator": "NoGpuHardwareConstraint"}, {"discriminator": "SSDHardwareConstraint"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(full.next_offset(4, 2), Some(6));
+        assert_eq!(full.next_offset(4, 3), None);
+
+        let empty: HardwareConstraintResponse =
+            serde_json::from_str(r#"{"data": [], "limit": 0}"#).unwrap();
+        assert_eq!(empty.next_offset(6, 2), None);
+    }
+
+    #[test]
+    fn variants_keep_their_fields() {
+        let page: HardwareConstraintResponse = serde_json::from_str(
+            r#"{"data": [
+                {"discriminator": "MinimumCoreHardwareConstraint", "coreCount": 16},
+                {"discriminator": "SpecificHardwareConstraint", "specificationKey": "16c-128g"},
+                {"discriminator": "QuantumHardwareConstraint", "qubits": 8}
+            ]}"#,
+        )
+        .unwrap();
+        let data = page.data.unwrap();
+        assert!(matches!(
+            &data[0],
+            HardwareConstraintVariant::MinimumCoreHardware(c) if c.core_count == Some(16)
+        ));
+        assert!(matches!(
+            &data[1],
+            HardwareConstraintVariant::SpecificHardware(c)
+                if c.specification_key.as_deref() == Some("16c-128g")
+        ));
+        assert!(matches!(&data[2], HardwareConstraintVariant::Unknown(_)));
+        assert_eq!(
+            serde_json::to_value(&data[2]).unwrap(),
+            serde_json::json!({"discriminator": "QuantumHardwareConstraint", "qubits": 8})
+        );
+    }
+}
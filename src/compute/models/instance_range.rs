@@ -0,0 +1,255 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// InstanceRange : Set of instance ids, written as `1-5,8,10-20` by the API
+///
+/// The ids are kept as sorted, disjoint and non adjacent intervals, so
+/// `3,1-2,5` is normalised to `1-3,5`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct InstanceRange {
+    /// Inclusive intervals of ids
+    intervals: Vec<(u32, u32)>,
+}
+
+/// The string is not a valid instance range
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseInstanceRangeError(pub String);
+
+impl fmt::Display for ParseInstanceRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid instance range {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseInstanceRangeError {}
+
+impl InstanceRange {
+    /// Empty range
+    pub const fn new() -> Self {
+        Self {
+            intervals: Vec::new(),
+        }
+    }
+
+    /// Ids from `start` to `end`, both included
+    pub fn from_bounds(start: u32, end: u32) -> Self {
+        Self::from_intervals(vec![(start.min(end), start.max(end))])
+    }
+
+    fn from_intervals(mut intervals: Vec<(u32, u32)>) -> Self {
+        intervals.sort_unstable();
+        let mut merged: Vec<(u32, u32)> = Vec::with_capacity(intervals.len());
+        for (start, end) in intervals {
+            match merged.last_mut() {
+                Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        Self { intervals: merged }
+    }
+
+    /// Number of ids in the range
+    pub fn len(&self) -> usize {
+        self.intervals
+            .iter()
+            .map(|(start, end)| (end - start) as usize + 1)
+            .sum()
+    }
+
+    /// Whether the range has no id
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Whether `id` is in the range
+    pub fn contains(&self, id: u32) -> bool {
+        self.intervals
+            .iter()
+            .any(|(start, end)| (*start..=*end).contains(&id))
+    }
+
+    /// Smallest and largest ids, `None` if empty
+    pub fn bounds(&self) -> Option<(u32, u32)> {
+        Some((self.intervals.first()?.0, self.intervals.last()?.1))
+    }
+
+    /// Ids in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.intervals.iter().flat_map(|(start, end)| *start..=*end)
+    }
+
+    /// Ids in `self` or `other`
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        Self::from_intervals([&self.intervals[..], &other.intervals[..]].concat())
+    }
+
+    /// Ids in both `self` and `other`
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while let (Some(a), Some(b)) = (self.intervals.get(i), other.intervals.get(j)) {
+            let (start, end) = (a.0.max(b.0), a.1.min(b.1));
+            if start <= end {
+                intervals.push((start, end));
+            }
+            if a.1 < b.1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { intervals }
+    }
+
+    /// Ids in `self` but not in `other`
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        for &(start, end) in &self.intervals {
+            let mut start = Some(start);
+            for &(other_start, other_end) in &other.intervals {
+                let Some(current) = start else { break };
+                if other_end < current || other_start > end {
+                    continue;
+                }
+                if other_start > current {
+                    intervals.push((current, other_start - 1));
+                }
+                start = other_end.checked_add(1).filter(|s| *s <= end);
+            }
+            if let Some(current) = start {
+                intervals.push((current, end));
+            }
+        }
+        Self { intervals }
+    }
+}
+
+impl FromStr for InstanceRange {
+    type Err = ParseInstanceRangeError;
+
+    /// Parse ids and intervals separated by commas, an empty string is an empty range
+    fn from_str(range: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseInstanceRangeError(range.to_owned());
+        if range.trim().is_empty() {
+            return Ok(Self::new());
+        }
+        let intervals = range
+            .split(',')
+            .map(|part| {
+                let (start, end) = part.split_once('-').unwrap_or((part, part));
+                let start: u32 = start.trim().parse().map_err(|_| invalid())?;
+                let end: u32 = end.trim().parse().map_err(|_| invalid())?;
+                if start > end {
+                    return Err(invalid());
+                }
+                Ok((start, end))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self::from_intervals(intervals))
+    }
+}
+
+impl TryFrom<String> for InstanceRange {
+    type Error = ParseInstanceRangeError;
+
+    fn try_from(range: String) -> Result<Self, Self::Error> {
+        range.parse()
+    }
+}
+
+/// Deserialize an optional range sent by the API, an invalid one is logged
+/// and read as `None` so that it does not reject the whole response
+pub(crate) fn deserialize_lenient<'de, D>(
+    deserializer: D,
+) -> Result<Option<InstanceRange>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let range = Option::<String>::deserialize(deserializer)?;
+    Ok(range.and_then(|r| r.parse().map_err(|e| warn!("{}", e)).ok()))
+}
+
+impl From<InstanceRange> for String {
+    fn from(range: InstanceRange) -> Self {
+        range.to_string()
+    }
+}
+
+impl fmt::Display for InstanceRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (start, end)) in self.intervals.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            if start == end {
+                write!(f, "{start}")?;
+            } else {
+                write!(f, "{start}-{end}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromIterator<u32> for InstanceRange {
+    fn from_iter<I: IntoIterator<Item = u32>>(ids: I) -> Self {
+        Self::from_intervals(ids.into_iter().map(|id| (id, id)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(range: &str) -> InstanceRange {
+        range.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_and_normalise() {
+        assert_eq!(range(" 10-20, 8,1-5,3-6 ").to_string(), "1-6,8,10-20");
+        assert_eq!(range("1,2,3").to_string(), "1-3");
+        assert_eq!(range("").len(), 0);
+        assert_eq!(range("1-5,8,10-20").len(), 17);
+        assert!(range("1-5,8").contains(8) && !range("1-5,8").contains(6));
+        assert_eq!(range("7,0-2").iter().collect::<Vec<_>>(), vec![0, 1, 2, 7]);
+        assert_eq!(range("7,0-2").bounds(), Some((0, 7)));
+        assert!("5-1".parse::<InstanceRange>().is_err());
+        assert!("1-".parse::<InstanceRange>().is_err());
+        assert!("a".parse::<InstanceRange>().is_err());
+        assert_eq!(
+            [4, 2, 3].into_iter().collect::<InstanceRange>(),
+            range("2-4")
+        );
+    }
+
+    #[test]
+    fn set_operations() {
+        let all = range("0-20");
+        let failed = range("2-4,10,15-30");
+        assert_eq!(all.union(&failed).to_string(), "0-30");
+        assert_eq!(all.intersection(&failed).to_string(), "2-4,10,15-20");
+        assert_eq!(all.difference(&failed).to_string(), "0-1,5-9,11-14");
+        assert_eq!(failed.difference(&all).to_string(), "21-30");
+        assert!(range("1-3").difference(&range("0-5")).is_empty());
+        assert_eq!(
+            range("0-4294967295")
+                .difference(&range("4294967295"))
+                .to_string(),
+            "0-4294967294"
+        );
+    }
+
+    #[test]
+    fn serde_as_string() {
+        let parsed: Option<InstanceRange> = serde_json::from_str("\"1-3,5\"").unwrap();
+        assert_eq!(parsed, Some(range("1-3,5")));
+        assert_eq!(serde_json::to_string(&range("5,1-3")).unwrap(), "\"1-3,5\"");
+        assert!(serde_json::from_str::<InstanceRange>("\"x\"").is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+/// `JobCreationInput` : Input of a job creation request
+#[derive(Clone, Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobCreationInput {
+    /// Given Name of the job.  <br />Should be less than 2048 characters.
+    pub name: String,
+    /// Shortname of the job (default is its Uuid).
+    /// <br />Should be <b>unique</b>.
+    /// > Available characters (all alphanumeric + hyphen): ```a-z```, ```A-Z```, ```0-9``` and ```-```.
+    /// > Should start with a letter or number and should not end with an hyphen (```-```).
+    pub shortname: Option<String>,
+    /// Uuid of the job's pool.
+    /// <br />Should be the uuid of an open pool.
+    /// <br />Tasks submitted to the job will automatically inherit it.
+    pub pool_uuid: Option<uuid::Uuid>,
+    /// Whether the tasks of the job can depend on each other.
+    pub use_dependencies: Option<bool>,
+    /// Maximum wall time of the job, after which it is terminated.
+    pub max_wall_time: Option<String>,
+    /// (Optional) List of tags  <br />Should be less than 10.
+    /// <br />The tags values should not be null and should be less than 512 characters.
+    pub tags: Option<Vec<String>>,
+    /// Job labels : arbitrary key / value pairs attached to the job in order to find it more easily.
+    pub labels: Option<std::collections::HashMap<String, String>>,
+    /// Whether the job should be deleted if completed and the job quota is reached
+    pub auto_delete_on_completion: Option<bool>,
+    /// Job life time limit
+    pub completion_time_to_live: Option<String>,
+}
+
+/// `JobOutput` : Description of a job
+#[derive(Clone, Default, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobOutput {
+    /// Uuid of the job
+    pub uuid: Option<uuid::Uuid>,
+    /// Given Name of the job
+    pub name: Option<String>,
+    /// Shortname of the job
+    pub shortname: Option<String>,
+    /// Uuid of the job's pool
+    pub pool_uuid: Option<uuid::Uuid>,
+    /// Current state of the job  <br>Can be: Active, Terminating, Completed, Terminated or Deleting
+    pub state: Option<String>,
+    /// Previous state of the job
+    pub previous_state: Option<String>,
+    /// Date of the last state transition
+    pub state_transition_date: Option<String>,
+    /// Date of the previous state transition
+    pub previous_state_transition_date: Option<String>,
+    /// Date of the job creation (UTC ISO 8601)
+    pub creation_date: Option<String>,
+    /// Date of the last modification
+    pub last_modified: Option<String>,
+    /// Whether the tasks of the job can depend on each other
+    pub use_dependencies: Option<bool>,
+    /// Maximum wall time of the job
+    pub max_wall_time: Option<String>,
+    /// (Optional) List of tags
+    pub tags: Option<Vec<String>>,
+    /// Job labels : arbitrary key / value pairs attached to the job in order to find it more easily.
+    pub labels: Option<std::collections::HashMap<String, String>>,
+    /// Whether the job should be deleted if the job quota is reached and the job is completed
+    pub auto_delete_on_completion: Option<bool>,
+    /// Job life time limit
+    pub completion_time_to_live: Option<String>,
+}
+
+impl JobCreationInput {
+    /// Input of a job creation request
+    pub const fn new(name: String) -> Self {
+        Self {
+            name,
+            shortname: None,
+            pool_uuid: None,
+            use_dependencies: None,
+            max_wall_time: None,
+            tags: None,
+            labels: None,
+            auto_delete_on_completion: None,
+            completion_time_to_live: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn job_output_deserialize() {
+        let example_str = r#"{
+          "uuid": "4bd5a8e4-7c3f-4a38-9b0d-3c5f7a6a8c21",
+          "name": "render job",
+          "shortname": "render-job",
+          "poolUuid": null,
+          "state": "Active",
+          "previousState": null,
+          "stateTransitionDate": "2024-01-10T10:00:00Z",
+          "previousStateTransitionDate": "0001-01-01T00:00:00Z",
+          "creationDate": "2024-01-10T10:00:00Z",
+          "lastModified": "2024-01-10T10:00:00Z",
+          "useDependencies": true,
+          "maxWallTime": "1.00:00:00",
+          "tags": ["render"],
+          "labels": null,
+          "autoDeleteOnCompletion": false,
+          "completionTimeToLive": "00:00:00"
+        }"#;
+        let job = serde_json::from_str::<JobOutput>(example_str);
+        assert!(job.is_ok(), "{:?}", job);
+    }
+}
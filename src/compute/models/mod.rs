@@ -6,15 +6,36 @@ pub mod completed_frame_output;
 pub use self::completed_frame_output::CompletedFrameOutput;
 /// Everything directly related to tasks routes
 pub mod task;
+pub use self::task::TaskCloneInput;
 pub use self::task::TaskCreationInput;
 pub use self::task::TaskOutput;
 pub use self::task::TaskRedoInput;
 pub use self::task::TaskSummaryOutput;
 pub use self::task::TaskUpdateInput;
+pub mod task_state;
+pub use self::task_state::StateTransition;
+pub use self::task_state::TaskState;
+/// Everything directly related to jobs routes
+pub mod job;
+pub use self::job::JobCreationInput;
+pub use self::job::JobOutput;
+/// Everything directly related to pools routes
+pub mod pool;
+pub use self::pool::PoolCreationInput;
+pub use self::pool::PoolElasticProperty;
+pub use self::pool::PoolOutput;
+pub use self::pool::PoolUpdateInput;
+/// Pages of the paginated routes
+pub mod pagination;
+pub use self::pagination::PaginatedResponse;
+pub mod instance_range;
+pub use self::instance_range::InstanceRange;
 pub mod resources_bucket;
 pub use self::resources_bucket::ResourcesBucket;
 pub mod qtask_status_output;
 pub use self::qtask_status_output::QTaskStatusOutput;
+pub mod secret;
+pub use self::secret::SecretValue;
 pub mod secrets_access_rights;
 pub use self::secrets_access_rights::SecretsAccessRights;
 pub mod hardware_constraint;
@@ -29,6 +50,7 @@ pub mod retry_settings;
 pub use self::retry_settings::RetrySettings;
 pub mod forced_constant;
 pub use self::forced_constant::ForcedConstant;
+pub use self::forced_constant::ForcedConstantAccess;
 pub mod dependency_input;
 pub use self::dependency_input::DependencyInput;
 pub mod profile;
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+/// Page of a token paginated route (`tasks/paginate`...)
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaginatedResponse<T> {
+    /// Items of the page
+    #[serde(default = "Vec::new")]
+    pub data: Vec<T>,
+    /// Token used to get this page
+    pub token: Option<String>,
+    /// Token to use to get the next page
+    pub next_page_token: Option<String>,
+    /// Whether there are more items after this page
+    pub is_truncated: Option<bool>,
+}
+
+impl<T> PaginatedResponse<T> {
+    /// Token of the next page, `None` on the last page
+    pub fn next_token(&self) -> Option<&str> {
+        if self.is_truncated == Some(false) {
+            return None;
+        }
+        self.next_page_token.as_deref().filter(|t| !t.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::models::TaskSummaryOutput;
+
+    #[test]
+    fn deserialize_pages() {
+        let page: PaginatedResponse<TaskSummaryOutput> = serde_json::from_str(
+            r#"{
+              "data": [{"uuid": "52c10b2d-0687-41e1-985e-7279f6dd543a", "name": "render"}],
+              "token": null,
+              "nextPageToken": "c2Vjb25k",
+              "isTruncated": true
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(page.data.len(), 1);
+        assert_eq!(page.next_token(), Some("c2Vjb25k"));
+
+        let last: PaginatedResponse<TaskSummaryOutput> =
+            serde_json::from_str(r#"{"data": [], "nextPageToken": "", "isTruncated": false}"#)
+                .unwrap();
+        assert_eq!(last.next_token(), None);
+    }
+}
@@ -0,0 +1,331 @@
+use crate::compute::models::qtask_status_output::QRunningInstancesInfoOutput;
+use crate::compute::models::{
+    Constants, ForcedConstant, HardwareConstraintVariant, Privileges, QError, ResourcesBucket,
+    RetrySettings, SchedulingClass, SecretsAccessRights,
+};
+use serde::{Deserialize, Serialize};
+
+/// `PoolCreationInput` : Input of a pool creation request
+#[derive(Clone, Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolCreationInput {
+    /// Given Name of the pool.  <br />Should be less than 2048 characters.
+    pub name: String,
+    /// Shortname of the pool (default is its Uuid).
+    /// <br />Should be <b>unique</b>.
+    /// > Available characters (all alphanumeric + hyphen): ```a-z```, ```A-Z```, ```0-9``` and ```-```.
+    /// > Should start with a letter or number and should not end with an hyphen (```-```).
+    pub shortname: Option<String>,
+    /// Profile used by the pool  <br />Should be one of the available profiles for the user.
+    pub profile: String,
+    /// Number of nodes of the pool.
+    /// <br />For elastic pools, this is the initial number of nodes.
+    pub instance_count: i32,
+    /// Names of the buckets containing resources for the pool.
+    /// <br /> The buckets should already exist in the user storage account.
+    /// <br />Should not be set with ```AdvancedResourceBuckets```.
+    pub resource_buckets: Option<Vec<String>>,
+    /// Names of the buckets containing resources for the pool, with custom prefix.
+    /// <br /> The buckets should already exist in the user storage account.
+    /// <br />Should not be set with ```ResourceBuckets```.
+    pub advanced_resource_buckets: Option<Vec<ResourcesBucket>>,
+    /// List of constants for the pool.
+    /// <br />They are used to configure the profiles and can be overridden to change its parameters.
+    pub constants: Option<Constants>,
+    /// List of constants to be overriden for the pool.
+    /// <br />This is expected to be used in a development context and requires specific permissions.
+    pub forced_constants: Option<Vec<ForcedConstant>>,
+    /// List of constraints for the pool (can be set only by Admin users)
+    pub constraints: Option<Constants>,
+    /// Constraints applied to hardware for executing the pool.
+    pub hardware_constraints: Option<Vec<HardwareConstraintVariant>>,
+    /// Describe secrets the task or pool will have access to when running.
+    pub secrets_access_rights: Option<SecretsAccessRights>,
+    /// (Optional) List of tags  <br />Should be less than 10.
+    /// <br />The tags values should not be null and should be less than 512 characters.
+    pub tags: Option<Vec<String>>,
+    /// Elastic settings of the pool
+    pub elastic_property: Option<PoolElasticProperty>,
+    /// Task run on every node of the pool when it joins the pool
+    pub preparation_task: Option<PoolPreparationTask>,
+    /// Default value of ```WaitForPoolResourcesSynchronization``` for the tasks of the pool
+    pub task_default_wait_for_pool_resources_synchronization: Option<bool>,
+    /// Whether the pool should be deleted if completed and the pool quota is reached
+    pub auto_delete_on_completion: Option<bool>,
+    /// Pool life time limit
+    pub completion_time_to_live: Option<String>,
+    /// Pool labels : arbitrary key / value pairs attached to the pool in order to find it more easily.
+    pub labels: Option<std::collections::HashMap<String, String>>,
+    pub scheduling_type: Option<SchedulingClass>,
+    /// The key of the reserved machine the pool should be dispatch on.
+    /// To use with ```\"reserved\"``` `scheduling_type`
+    pub targeted_reserved_machine_key: Option<String>,
+    /// The default TTL value for all the pool resources cache.  TTL is 7776000s by default.
+    #[serde(rename = "defaultResourcesCacheTTLSec")]
+    pub default_resources_cache_ttl_sec: Option<i32>,
+    pub privileges: Option<Privileges>,
+    /// Default retry settings of the tasks of the pool
+    pub default_retry_settings: Option<RetrySettings>,
+}
+
+/// `PoolElasticProperty` : Elastic settings of a pool
+#[derive(Clone, Default, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolElasticProperty {
+    /// Whether the pool is elastic
+    pub is_elastic: Option<bool>,
+    /// Minimum number of nodes of the pool
+    pub min_total_slots: Option<i32>,
+    /// Maximum number of nodes of the pool
+    pub max_total_slots: Option<i32>,
+    /// Minimum number of idle nodes to keep in the pool
+    pub min_idle_slots: Option<i32>,
+    /// Period (in seconds) between two resizes of the pool
+    pub resize_period: Option<i32>,
+    /// Fraction of the missing nodes to add at each resize
+    pub ramp_resize_factor: Option<f32>,
+    /// Time (in seconds) a node can stay idle before being released
+    pub min_idle_time_seconds: Option<i32>,
+}
+
+/// `PoolPreparationTask` : Task run on every node of the pool when it joins the pool
+#[derive(Clone, Default, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolPreparationTask {
+    /// Command line of the preparation task
+    pub command_line: Option<String>,
+}
+
+/// `PoolOutput` : Description of a pool
+#[derive(Clone, Default, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolOutput {
+    /// (Optional) Errors
+    pub errors: Option<Vec<QError>>,
+    /// Names of the buckets containing resources for the pool
+    pub resource_buckets: Option<Vec<String>>,
+    pub advanced_resource_buckets: Option<Vec<ResourcesBucket>>,
+    pub status: Option<PoolStatusOutput>,
+    /// List of constants for the pool.  All secret constants with the prefix \"QARNOT_SECRET__\" will display \"[SECRET]\" value.
+    pub constants: Option<Constants>,
+    pub secrets_access_rights: Option<SecretsAccessRights>,
+    /// (Optional) List of tags
+    pub tags: Option<Vec<String>>,
+    /// Elastic settings of the pool
+    pub elastic_property: Option<PoolElasticProperty>,
+    /// Task run on every node of the pool when it joins the pool
+    pub preparation_task: Option<PoolPreparationTask>,
+    /// Default value of ```WaitForPoolResourcesSynchronization``` for the tasks of the pool
+    pub task_default_wait_for_pool_resources_synchronization: Option<bool>,
+    /// Whether the pool should be deleted if the pool quota is reached and the pool is completed
+    pub auto_delete_on_completion: Option<bool>,
+    /// Pool life time limit
+    pub completion_time_to_live: Option<String>,
+    /// Constraints applied to hardware for executing the pool
+    pub hardware_constraints: Option<Vec<HardwareConstraintVariant>>,
+    /// Pool labels : arbitrary key / value pairs attached to the pool in order to find it more easily.
+    pub labels: Option<std::collections::HashMap<String, String>>,
+    pub scheduling_type: Option<SchedulingClass>,
+    /// The key of the reserved machine the user wanted the pool to be dispatched on.
+    pub targeted_reserved_machine_key: Option<String>,
+    pub privileges: Option<Privileges>,
+    pub default_retry_settings: Option<RetrySettings>,
+    /// Uuid of the pool
+    pub uuid: Option<uuid::Uuid>,
+    /// Given Name of the pool
+    pub name: Option<String>,
+    /// Shortname of the pool
+    pub shortname: Option<String>,
+    /// Profile used by the pool
+    pub profile: Option<String>,
+    /// Number of nodes of the pool
+    pub instance_count: Option<i32>,
+    /// Number of nodes of the pool that are currently running
+    pub running_instance_count: Option<i32>,
+    /// Number of cores currently used by the pool nodes
+    pub running_core_count: Option<i32>,
+    /// Current execution time
+    pub execution_time: Option<String>,
+    /// Current state of the pool  <br>Can be: Submitted, PartiallyDispatched, FullyDispatched, PartiallyExecuting,  FullyExecuting, Closing, Closed, Failure or PendingDelete
+    pub state: Option<String>,
+    /// Previous state of the pool
+    pub previous_state: Option<String>,
+    /// Date of the last state transition
+    pub state_transition_time: Option<String>,
+    /// Date of the previous state transition
+    pub previous_state_transition_time: Option<String>,
+    /// Date of the last modification
+    pub last_modified: Option<String>,
+    /// Date of the pool creation (UTC ISO 8601)
+    pub creation_date: Option<String>,
+    /// Date of the pool end
+    pub end_date: Option<String>,
+}
+
+/// `PoolStatusOutput` : Detail of the pool execution status
+#[derive(Clone, Default, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStatusOutput {
+    /// Date of the status update
+    pub timestamp: Option<String>,
+    /// Date of the last status update
+    pub last_update_timestamp: Option<String>,
+    /// Resources download progress.
+    pub download_progress: Option<f32>,
+    /// Execution progress.
+    pub execution_progress: Option<f32>,
+    /// Upload progress.
+    pub upload_progress: Option<f32>,
+    /// Number of running nodes.
+    pub instance_count: Option<i32>,
+    /// Time of the resources download.
+    pub download_time: Option<String>,
+    /// Time of the resources download in seconds.
+    pub download_time_sec: Option<f64>,
+    /// Environment set up time, ie. the time needed to boot.
+    pub environment_time: Option<String>,
+    /// Time of the environment setup in seconds.
+    pub environment_time_sec: Option<f64>,
+    /// Execution time
+    pub execution_time: Option<String>,
+    /// Execution time in seconds
+    pub execution_time_sec: Option<f64>,
+    /// Pool's wall time.
+    pub wall_time: Option<String>,
+    /// Pool's wall time in seconds.
+    pub wall_time_sec: Option<f64>,
+    pub running_instances_info: Option<Box<QRunningInstancesInfoOutput>>,
+}
+
+/// `PoolUpdateInput` : Fields of the pool to update
+#[derive(Clone, Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolUpdateInput {
+    /// List of new constants for the pool
+    pub constants: Option<Constants>,
+    /// List of new constraints for the pool (only available for Admin user)
+    pub constraints: Option<Constants>,
+    /// List of new tags for the pool
+    pub tags: Option<Vec<String>>,
+    /// New elastic settings of the pool
+    pub elastic_property: Option<PoolElasticProperty>,
+}
+
+impl PoolCreationInput {
+    /// Input of a pool creation request
+    pub const fn new(name: String, profile: String, instance_count: i32) -> Self {
+        Self {
+            name,
+            shortname: None,
+            profile,
+            instance_count,
+            resource_buckets: None,
+            advanced_resource_buckets: None,
+            constants: None,
+            forced_constants: None,
+            constraints: None,
+            hardware_constraints: None,
+            secrets_access_rights: None,
+            tags: None,
+            elastic_property: None,
+            preparation_task: None,
+            task_default_wait_for_pool_resources_synchronization: None,
+            auto_delete_on_completion: None,
+            completion_time_to_live: None,
+            labels: None,
+            scheduling_type: None,
+            targeted_reserved_machine_key: None,
+            default_resources_cache_ttl_sec: None,
+            privileges: None,
+            default_retry_settings: None,
+        }
+    }
+}
+
+impl PoolElasticProperty {
+    /// Elastic settings of a pool
+    pub const fn new() -> Self {
+        Self {
+            is_elastic: None,
+            min_total_slots: None,
+            max_total_slots: None,
+            min_idle_slots: None,
+            resize_period: None,
+            ramp_resize_factor: None,
+            min_idle_time_seconds: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pool_output_deserialize() {
+        let example_str = r#"{
+          "errors": [],
+          "resourceBuckets": ["my-input-bucket"],
+          "advancedResourceBuckets": null,
+          "status": {
+            "timestamp": "0001-01-01T00:00:00Z",
+            "lastUpdateTimestamp": "0001-01-01T00:00:00Z",
+            "downloadProgress": 100,
+            "executionProgress": 0,
+            "uploadProgress": 0,
+            "instanceCount": 2,
+            "downloadTime": "00:00:00",
+            "downloadTimeSec": 0,
+            "environmentTime": "00:01:10",
+            "environmentTimeSec": 70,
+            "executionTime": "00:00:00",
+            "executionTimeSec": 0,
+            "wallTime": "00:01:30",
+            "wallTimeSec": 90,
+            "runningInstancesInfo": null
+          },
+          "constants": [
+            {
+              "key": "DOCKER_CMD",
+              "value": "sleep infinity"
+            }
+          ],
+          "tags": ["render"],
+          "elasticProperty": {
+            "isElastic": true,
+            "minTotalSlots": 0,
+            "maxTotalSlots": 10,
+            "minIdleSlots": 0,
+            "resizePeriod": 90,
+            "rampResizeFactor": 0.4,
+            "minIdleTimeSeconds": 90
+          },
+          "preparationTask": null,
+          "taskDefaultWaitForPoolResourcesSynchronization": false,
+          "autoDeleteOnCompletion": false,
+          "completionTimeToLive": "00:00:00",
+          "hardwareConstraints": null,
+          "labels": null,
+          "schedulingType": "flex",
+          "privileges": null,
+          "defaultRetrySettings": null,
+          "uuid": "0fb4bd34-45a1-4b4a-8d73-2b0e4c3e2a7d",
+          "name": "my pool",
+          "shortname": "my-pool",
+          "profile": "docker-batch",
+          "instanceCount": 2,
+          "runningInstanceCount": 2,
+          "runningCoreCount": 32,
+          "executionTime": "00:00:00",
+          "state": "FullyExecuting",
+          "previousState": "FullyDispatched",
+          "stateTransitionTime": "2024-01-10T10:00:00Z",
+          "previousStateTransitionTime": "2024-01-10T09:58:00Z",
+          "lastModified": "2024-01-10T10:00:00Z",
+          "creationDate": "2024-01-10T09:55:00Z",
+          "endDate": "0001-01-01T00:00:00Z"
+        }"#;
+        let pool = serde_json::from_str::<PoolOutput>(example_str);
+        assert!(pool.is_ok(), "{:?}", pool);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::compute::models::instance_range::deserialize_lenient;
+use crate::compute::models::InstanceRange;
 use serde::{Deserialize, Serialize};
 
 /// QTaskStatusOutput : Detail of the task execution status
@@ -45,17 +47,23 @@ pub struct QTaskStatusOutput {
     /// Task's wall time in seconds.
     pub wall_time_sec: Option<f64>,
     /// Succeeded instances range.
-    pub succeeded_range: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    pub succeeded_range: Option<InstanceRange>,
     /// Executed instances range, ie. the succeeded range plus the failed range.
-    pub executed_range: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    pub executed_range: Option<InstanceRange>,
     /// Failed instances range, if there is any.
-    pub failed_range: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    pub failed_range: Option<InstanceRange>,
     /// Cancelled instances range, if there is any.
-    pub cancelled_range: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    pub cancelled_range: Option<InstanceRange>,
     /// Failed instances range without the cancelled range, if there is any.
-    pub failed_only_range: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    pub failed_only_range: Option<InstanceRange>,
     /// Instances that have started at least once, used for minimum billing
-    pub started_once_range: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    pub started_once_range: Option<InstanceRange>,
     pub running_instances_info: Option<Box<QRunningInstancesInfoOutput>>,
 }
 
@@ -252,9 +260,10 @@ pub struct QTaskActiveForwardOutput {
 
 /// QTaskExecutionPhaseOutput : Possible execution state of the task
 /// Possible execution state of the task
-#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum QTaskExecutionPhaseOutput {
+    #[default]
     Download,
     Dispatch,
     Environment,
@@ -276,12 +285,6 @@ impl std::fmt::Display for QTaskExecutionPhaseOutput {
     }
 }
 
-impl Default for QTaskExecutionPhaseOutput {
-    fn default() -> Self {
-        Self::Download
-    }
-}
-
 /// TaskVpnConnectionOutput : Vpn connection of the task
 #[derive(Clone, Default, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -392,4 +395,16 @@ mod tests {
         let deser = serde_json::from_str::<QTaskStatusOutput>(example_str);
         assert!(deser.is_ok(), "{:?}", deser);
     }
+
+    #[test]
+    fn invalid_ranges_are_skipped() {
+        let status: QTaskStatusOutput = serde_json::from_str(
+            r#"{"failedRange": "2-x", "succeededRange": "0-1,3", "cancelledRange": null}"#,
+        )
+        .unwrap();
+        assert_eq!(status.failed_range, None);
+        assert_eq!(status.succeeded_range.map(|r| r.len()), Some(3));
+        assert_eq!(status.cancelled_range, None);
+        assert_eq!(status.executed_range, None);
+    }
 }
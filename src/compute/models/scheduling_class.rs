@@ -2,9 +2,10 @@ use serde::{Deserialize, Serialize};
 
 /// SchedulingClass : Type of scheduling used when dispatching the tasks
 /// Type of scheduling used when dispatching the tasks
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum SchedulingClass {
+    #[default]
     Flex,
     OnDemand,
     Reserved,
@@ -19,9 +20,3 @@ impl std::fmt::Display for SchedulingClass {
         }
     }
 }
-
-impl Default for SchedulingClass {
-    fn default() -> Self {
-        Self::Flex
-    }
-}
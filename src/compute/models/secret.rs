@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// SecretValue : Value of a secret, as sent to and returned by the secrets manager
+/// The value is hidden from the `Debug` output, so it does not end up in logs.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct SecretValue {
+    pub value: String,
+}
+
+impl fmt::Debug for SecretValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SecretValue")
+            .field("value", &"[SECRET]")
+            .finish()
+    }
+}
@@ -23,3 +23,66 @@ pub struct PrefixSecretAccessRight {
     /// Prefix used for filtering
     pub prefix: Option<String>,
 }
+
+impl SecretsAccessRights {
+    /// Give access to the secret `key`
+    pub fn add_secret(&mut self, key: &str) -> &mut Self {
+        let secrets = self.by_secret.get_or_insert_with(Vec::new);
+        if !secrets.iter().any(|s| s.key.as_deref() == Some(key)) {
+            secrets.push(ExactSecretAccessRight {
+                key: Some(key.to_owned()),
+            });
+        }
+        self
+    }
+
+    /// Give access to every secret starting with `prefix`
+    pub fn add_prefix(&mut self, prefix: &str) -> &mut Self {
+        let prefixes = self.by_prefix.get_or_insert_with(Vec::new);
+        if !prefixes.iter().any(|p| p.prefix.as_deref() == Some(prefix)) {
+            prefixes.push(PrefixSecretAccessRight {
+                prefix: Some(prefix.to_owned()),
+            });
+        }
+        self
+    }
+
+    /// Whether the secret `key` is accessible with these rights
+    pub fn allows(&self, key: &str) -> bool {
+        self.by_secret
+            .iter()
+            .flatten()
+            .any(|s| s.key.as_deref() == Some(key))
+            || self
+                .by_prefix
+                .iter()
+                .flatten()
+                .filter_map(|p| p.prefix.as_deref())
+                .any(|prefix| key.starts_with(prefix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn declare_secrets() {
+        let mut rights = SecretsAccessRights::default();
+        rights
+            .add_secret("db/password")
+            .add_secret("db/password")
+            .add_prefix("render/");
+        assert_eq!(rights.by_secret.as_ref().map(Vec::len), Some(1));
+        assert!(rights.allows("db/password"));
+        assert!(rights.allows("render/license/key"));
+        assert!(!rights.allows("db/user"));
+        assert_eq!(
+            serde_json::to_value(&rights).unwrap(),
+            serde_json::json!({
+                "bySecret": [{"key": "db/password"}],
+                "byPrefix": [{"prefix": "render/"}],
+            })
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use crate::compute::models::{
     CompletedFrameOutput, Constants, DependencyInput, ForcedConstant, HardwareConstraintVariant,
     Privileges, QError, QTaskStatusOutput, ResourcesBucket, RetrySettings, SchedulingClass,
-    SecretsAccessRights,
+    SecretsAccessRights, TaskState,
 };
 use serde::{Deserialize, Serialize};
 
@@ -192,9 +192,9 @@ pub struct TaskOutput {
     /// Limit execution time for the task
     pub wall_time: Option<String>,
     /// Current state of the task  <br>Can be: Submitted, PartiallyDispatched, FullyDispatched, PartiallyExecuting,  FullyExecuting, UploadingResults, Cancelled, Success, Failure, PendingDelete or PendingCancel
-    pub state: Option<String>,
+    pub state: Option<TaskState>,
     /// Previous state of the task
-    pub previous_state: Option<String>,
+    pub previous_state: Option<TaskState>,
     /// Number of task instances
     pub instance_count: Option<i32>,
     /// Range of task instances  <br>(Optional - replace `instance_count`) Specify an advanced range instead of a InstanceCount
@@ -239,9 +239,9 @@ pub struct TaskSummaryOutput {
     /// Limit execution time for the task
     pub wall_time: Option<String>,
     /// Current state of the task  <br>Can be: Submitted, PartiallyDispatched, FullyDispatched, PartiallyExecuting,  FullyExecuting, UploadingResults, Cancelled, Success, Failure, PendingDelete or PendingCancel
-    pub state: Option<String>,
+    pub state: Option<TaskState>,
     /// Previous state of the task
-    pub previous_state: Option<String>,
+    pub previous_state: Option<TaskState>,
     /// Number of task instances
     pub instance_count: Option<i32>,
     /// Range of task instances  <br>(Optional - replace InstanceCount) Specify an advanced range instead of a InstanceCount
@@ -444,15 +444,40 @@ pub struct TaskCloneInput {
 }
 
 /// `TaskUpdateInput` : Fields of the task to update
+/// Fields left to `None` are not sent and keep their current value.
 #[derive(Clone, Default, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskUpdateInput {
-    /// List of new constants for the pool
+    /// List of new constants for the task
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub constants: Option<Constants>,
-    /// List of new constraints for the pool (only available for Admin user)
+    /// List of new constraints for the task (only available for Admin user)
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub constraints: Option<Constants>,
-    /// List of new tags for the pool
+    /// List of new tags for the task
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<Vec<String>>,
+    /// New task labels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<std::collections::HashMap<String, String>>,
+    /// Regex of whitelisted files for snapshots
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot_whitelist: Option<String>,
+    /// Regex of blacklisted files for snapshots
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot_blacklist: Option<String>,
+    /// Regex of whitelisted files for results
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub results_whitelist: Option<String>,
+    /// Regex of blacklisted files for results
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub results_blacklist: Option<String>,
+    /// Whether the results should be uploaded if the task is cancelled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upload_results_on_cancellation: Option<bool>,
+    /// New retry settings of the task instances
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_settings: Option<RetrySettings>,
 }
 
 //XXX IMPLEMENATIONS
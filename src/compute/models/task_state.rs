@@ -0,0 +1,131 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// TaskState : State of a task
+/// States unknown to this client are kept in `Unknown` instead of being
+/// mistaken for another state.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum TaskState {
+    Submitted,
+    PartiallyDispatched,
+    FullyDispatched,
+    PartiallyExecuting,
+    FullyExecuting,
+    DownloadingResults,
+    UploadingResults,
+    Cancelled,
+    Success,
+    Failure,
+    PendingDelete,
+    PendingCancel,
+    Unknown(String),
+}
+
+impl From<&str> for TaskState {
+    fn from(state: &str) -> Self {
+        match state {
+            "Submitted" => Self::Submitted,
+            "PartiallyDispatched" => Self::PartiallyDispatched,
+            "FullyDispatched" => Self::FullyDispatched,
+            "PartiallyExecuting" => Self::PartiallyExecuting,
+            "FullyExecuting" => Self::FullyExecuting,
+            "DownloadingResults" => Self::DownloadingResults,
+            "UploadingResults" => Self::UploadingResults,
+            "Cancelled" => Self::Cancelled,
+            "Success" => Self::Success,
+            "Failure" => Self::Failure,
+            "PendingDelete" => Self::PendingDelete,
+            "PendingCancel" => Self::PendingCancel,
+            _ => Self::Unknown(state.to_owned()),
+        }
+    }
+}
+
+impl From<String> for TaskState {
+    fn from(state: String) -> Self {
+        Self::from(state.as_str())
+    }
+}
+
+impl From<TaskState> for String {
+    fn from(state: TaskState) -> Self {
+        state.to_string()
+    }
+}
+
+impl std::fmt::Display for TaskState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Submitted => write!(f, "Submitted"),
+            Self::PartiallyDispatched => write!(f, "PartiallyDispatched"),
+            Self::FullyDispatched => write!(f, "FullyDispatched"),
+            Self::PartiallyExecuting => write!(f, "PartiallyExecuting"),
+            Self::FullyExecuting => write!(f, "FullyExecuting"),
+            Self::DownloadingResults => write!(f, "DownloadingResults"),
+            Self::UploadingResults => write!(f, "UploadingResults"),
+            Self::Cancelled => write!(f, "Cancelled"),
+            Self::Success => write!(f, "Success"),
+            Self::Failure => write!(f, "Failure"),
+            Self::PendingDelete => write!(f, "PendingDelete"),
+            Self::PendingCancel => write!(f, "PendingCancel"),
+            Self::Unknown(state) => write!(f, "{state}"),
+        }
+    }
+}
+
+impl TaskState {
+    /// Whether the task may still change state on its own
+    /// Unknown states are considered running, so waiting for the task
+    /// does not stop on a state this client does not know about.
+    pub const fn is_running_or_downloading(&self) -> bool {
+        matches!(
+            self,
+            Self::Submitted
+                | Self::PartiallyDispatched
+                | Self::FullyDispatched
+                | Self::PartiallyExecuting
+                | Self::FullyExecuting
+                | Self::DownloadingResults
+                | Self::UploadingResults
+                | Self::Unknown(_)
+        )
+    }
+
+    /// Whether the task is finished (Success/Cancelled/Failure)
+    pub const fn is_terminal(&self) -> bool {
+        matches!(self, Self::Success | Self::Cancelled | Self::Failure)
+    }
+
+    /// Whether the task finished successfully
+    pub const fn is_success(&self) -> bool {
+        matches!(self, Self::Success)
+    }
+}
+
+/// Entry of the state history of a task
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateTransition {
+    pub state: TaskState,
+    /// Date the task entered `state`
+    pub time: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_states_are_preserved() {
+        let state: TaskState = serde_json::from_str("\"FullyExecuting\"").unwrap();
+        assert_eq!(state, TaskState::FullyExecuting);
+        let state: TaskState = serde_json::from_str("\"Hibernating\"").unwrap();
+        assert_eq!(state, TaskState::Unknown(String::from("Hibernating")));
+        assert!(state.is_running_or_downloading());
+        assert!(!state.is_terminal());
+        assert_eq!(serde_json::to_string(&state).unwrap(), "\"Hibernating\"");
+        assert_eq!(TaskState::PendingCancel.to_string(), "PendingCancel");
+        assert!(TaskState::Success.is_terminal() && TaskState::Success.is_success());
+        assert!(TaskState::Failure.is_terminal() && !TaskState::Failure.is_success());
+    }
+}
@@ -0,0 +1,398 @@
+use std::str::FromStr;
+
+use crate::compute::client::ComputeClient;
+use crate::compute::models::{
+    Constants, HardwareConstraintVariant, PoolCreationInput, PoolElasticProperty, PoolOutput,
+    PoolUpdateInput, QError, ResourcesBucket,
+};
+use crate::compute::task::{InstancesOrRange, ProfileOrPool, Task};
+use crate::compute::ComputeError;
+use crate::storage::StorageClient;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// State of a pool
+/// States unknown to this client are kept in `Unknown` instead of being
+/// mistaken for another state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PoolState {
+    Submitted,
+    PartiallyDispatched,
+    FullyDispatched,
+    PartiallyExecuting,
+    FullyExecuting,
+    Closing,
+    Closed,
+    Failure,
+    PendingDelete,
+    Unknown(String),
+}
+
+impl From<&str> for PoolState {
+    fn from(state: &str) -> Self {
+        match state {
+            "Submitted" => Self::Submitted,
+            "PartiallyDispatched" => Self::PartiallyDispatched,
+            "FullyDispatched" => Self::FullyDispatched,
+            "PartiallyExecuting" => Self::PartiallyExecuting,
+            "FullyExecuting" => Self::FullyExecuting,
+            "Closing" => Self::Closing,
+            "Closed" => Self::Closed,
+            "Failure" => Self::Failure,
+            "PendingDelete" => Self::PendingDelete,
+            _ => Self::Unknown(state.to_owned()),
+        }
+    }
+}
+
+impl PoolState {
+    /// Whether the pool accepts and executes tasks
+    pub const fn is_open(&self) -> bool {
+        matches!(self, Self::PartiallyExecuting | Self::FullyExecuting)
+    }
+
+    /// Whether the pool is being or has been shut down
+    pub const fn is_closed_or_failed(&self) -> bool {
+        matches!(
+            self,
+            Self::Closing | Self::Closed | Self::Failure | Self::PendingDelete
+        )
+    }
+}
+
+/// High level wrapper around everything around pools
+/// This is returned by QarnotClient::create_pool()
+/// Use `.run()` to submit the pool
+/// Use `.wait_until_open()` to wait for the pool to accept tasks
+/// Use `.create_task()` to create tasks running in the pool
+/// Use `.close()` to release the pool nodes
+pub struct Pool<'a> {
+    compute_client: &'a ComputeClient,
+    storage: Option<&'a dyn StorageClient>,
+    pub name: String,
+    pub shortname: Option<String>,
+    pub profile: String,
+    pub instance_count: i32,
+    pub resource_buckets: Option<Vec<String>>,
+    pub advanced_resource_buckets: Option<Vec<ResourcesBucket>>,
+    pub constants: Option<Constants>,
+    pub constraints: Option<Constants>,
+    pub hardware_constraints: Option<Vec<HardwareConstraintVariant>>,
+    pub tags: Option<Vec<String>>,
+    pub labels: Option<std::collections::HashMap<String, String>>,
+    pub elastic_property: Option<PoolElasticProperty>,
+    pub task_default_wait_for_pool_resources_synchronization: Option<bool>,
+    pub auto_delete: bool,
+    pub update_cache_time: TimeDelta,
+    pub last_cache: DateTime<Utc>,
+    pub state: Option<PoolState>,
+    pub previous_state: Option<String>,
+    pub uuid: Option<uuid::Uuid>,
+    pub running_instance_count: u32,
+    pub running_core_count: u32,
+    pub errors: Option<Vec<QError>>,
+    pub last_modified: Option<DateTime<Utc>>,
+    pub end_date: Option<DateTime<Utc>>,
+}
+
+impl<'a> Pool<'a> {
+    /// Set the storage client given to the tasks of the pool
+    /// This is done by QarnotClient::create_pool()
+    pub fn set_storage(&mut self, storage: Option<&'a dyn StorageClient>) {
+        self.storage = storage;
+    }
+
+    pub fn new(
+        compute_client: &'a ComputeClient,
+        name: &str,
+        profile: &str,
+        shortname: Option<String>,
+        instance_count: i32,
+    ) -> Self {
+        Self {
+            compute_client,
+            storage: None,
+            name: name.to_owned(),
+            shortname,
+            profile: profile.to_owned(),
+            instance_count,
+            resource_buckets: None,
+            advanced_resource_buckets: None,
+            constants: None,
+            constraints: None,
+            hardware_constraints: None,
+            tags: None,
+            labels: None,
+            elastic_property: None,
+            task_default_wait_for_pool_resources_synchronization: None,
+            auto_delete: false,
+            update_cache_time: TimeDelta::seconds(5),
+            last_cache: Utc::now(),
+            state: None,
+            previous_state: None,
+            uuid: None,
+            running_instance_count: 0,
+            running_core_count: 0,
+            errors: None,
+            last_modified: None,
+            end_date: None,
+        }
+    }
+
+    /// Make the pool elastic
+    ///
+    /// # Arguments
+    /// * `min_slots` - Minimum number of nodes of the pool
+    /// * `max_slots` - Maximum number of nodes of the pool
+    /// * `resize_period` - Time between two resizes of the pool
+    pub fn set_elastic(&mut self, min_slots: i32, max_slots: i32, resize_period: TimeDelta) {
+        let mut elastic = self.elastic_property.take().unwrap_or_default();
+        elastic.is_elastic = Some(true);
+        elastic.min_total_slots = Some(min_slots);
+        elastic.max_total_slots = Some(max_slots);
+        elastic.resize_period = i32::try_from(resize_period.num_seconds()).ok();
+        self.elastic_property = Some(elastic);
+    }
+
+    /// Run pool
+    pub async fn run(&mut self) -> Result<(), ComputeError> {
+        let mut input =
+            PoolCreationInput::new(self.name.clone(), self.profile.clone(), self.instance_count);
+        input.shortname = self.shortname.clone();
+        input.resource_buckets = self.resource_buckets.clone();
+        input.advanced_resource_buckets = self.advanced_resource_buckets.clone();
+        input.constants = self.constants.clone();
+        input.constraints = self.constraints.clone();
+        input.hardware_constraints = self.hardware_constraints.clone();
+        input.tags = self.tags.clone();
+        input.labels = self.labels.clone();
+        input.elastic_property = self.elastic_property.clone();
+        input.task_default_wait_for_pool_resources_synchronization =
+            self.task_default_wait_for_pool_resources_synchronization;
+        input.auto_delete_on_completion = Some(self.auto_delete);
+        let res = self.compute_client.post_pool(input).await?;
+        self.uuid = res.uuid;
+        if self.shortname.is_none() {
+            self.shortname = res.uuid.map(|u| u.to_string());
+        };
+        Ok(())
+    }
+
+    /// Wait for the pool to be open, ie. ready to execute tasks
+    /// Unknown states are waited through, like the submission states.
+    ///
+    /// # Errors
+    /// * `ComputeError::NotSubmitted` - The pool was not submitted with `.run()`
+    /// * `ComputeError::PoolClosed(_)` - The pool closed or failed before opening
+    /// * `ComputeError::_` - The pool could not be updated
+    pub async fn wait_until_open(&mut self) -> Result<(), ComputeError> {
+        if self.uuid.is_none() {
+            error!("No uuid, have you started the pool ?");
+            return Err(ComputeError::NotSubmitted);
+        }
+        loop {
+            self.get_update(true).await?;
+            match &self.state {
+                Some(state) if state.is_open() => return Ok(()),
+                Some(state) if state.is_closed_or_failed() => {
+                    error!("Pool closed before opening: {:?}", self.errors);
+                    return Err(ComputeError::PoolClosed(
+                        self.errors.clone().unwrap_or_default(),
+                    ));
+                }
+                _ => {
+                    let delay = self.update_cache_time.to_std().unwrap_or_default();
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+
+    /// Create a new task running in this pool
+    ///
+    /// # Errors
+    /// * `ComputeError::NotSubmitted` - The pool was not submitted with `.run()`
+    pub fn create_task(
+        &self,
+        name: &str,
+        shortname: Option<String>,
+        instance_or_range: InstancesOrRange,
+    ) -> Result<Task<'a>, ComputeError> {
+        let Some(uuid) = self.uuid else {
+            error!("No uuid, have you started the pool ?");
+            return Err(ComputeError::NotSubmitted);
+        };
+        let mut task = Task::new(
+            self.compute_client,
+            name,
+            ProfileOrPool::Pool(uuid),
+            shortname,
+            instance_or_range,
+        );
+        task.set_storage(self.storage);
+        Ok(task)
+    }
+
+    /// Update current struct with values from a PoolOutput
+    fn update_fields(&mut self, updated_pool: PoolOutput) {
+        if let Some(name) = updated_pool.name {
+            self.name = name;
+        }
+        self.shortname = updated_pool.shortname;
+        if let Some(profile) = updated_pool.profile {
+            self.profile = profile;
+        }
+        if let Some(instance_count) = updated_pool.instance_count {
+            self.instance_count = instance_count;
+        }
+        self.resource_buckets = updated_pool.resource_buckets;
+        self.advanced_resource_buckets = updated_pool.advanced_resource_buckets;
+        self.constants = updated_pool.constants;
+        self.hardware_constraints = updated_pool.hardware_constraints;
+        self.tags = updated_pool.tags;
+        self.labels = updated_pool.labels;
+        self.elastic_property = updated_pool.elastic_property;
+        self.task_default_wait_for_pool_resources_synchronization =
+            updated_pool.task_default_wait_for_pool_resources_synchronization;
+        if let Some(auto_delete) = updated_pool.auto_delete_on_completion {
+            self.auto_delete = auto_delete;
+        }
+        self.uuid = updated_pool.uuid;
+        self.state = updated_pool.state.map(|s| PoolState::from(s.as_str()));
+        self.previous_state = updated_pool.previous_state;
+        self.running_instance_count = updated_pool
+            .running_instance_count
+            .and_then(|c| u32::try_from(c).ok())
+            .unwrap_or_default();
+        self.running_core_count = updated_pool
+            .running_core_count
+            .and_then(|c| u32::try_from(c).ok())
+            .unwrap_or_default();
+        self.errors = updated_pool.errors;
+        self.last_modified = updated_pool
+            .last_modified
+            .map(|s| DateTime::<Utc>::from_str(s.as_str()).unwrap_or_default());
+        self.end_date = updated_pool
+            .end_date
+            .map(|s| DateTime::<Utc>::from_str(s.as_str()).unwrap_or_default());
+    }
+
+    /// Update struct with changes from the API
+    pub async fn get_update(&mut self, force_update: bool) -> Result<(), ComputeError> {
+        if let Some(uuid) = self.uuid {
+            if force_update || Utc::now() - self.last_cache >= self.update_cache_time {
+                let res = self.compute_client.get_pool_info(uuid).await?;
+                self.update_fields(res);
+                self.last_cache = Utc::now();
+            }
+        }
+        Ok(())
+    }
+
+    /// Push changes to the struct to the compute API
+    /// This results in PUT /pools/{uuid}
+    pub async fn commit(&self) -> Result<(), ComputeError> {
+        if let Some(uuid) = self.uuid {
+            let input = PoolUpdateInput {
+                constants: self.constants.clone(),
+                constraints: self.constraints.clone(),
+                tags: self.tags.clone(),
+                elastic_property: self.elastic_property.clone(),
+            };
+            self.compute_client.put_update_pool(uuid, input).await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Make the pool nodes synchronize their resources with the resource buckets
+    pub async fn update_resources(&self) -> Result<(), ComputeError> {
+        if let Some(uuid) = self.uuid {
+            self.compute_client.patch_update_pool_resources(uuid).await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Close the pool, releasing its nodes
+    pub async fn close(&self) -> Result<(), ComputeError> {
+        if let Some(uuid) = self.uuid {
+            self.compute_client.post_close_pool(uuid).await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Delete the pool
+    pub async fn delete(&self) -> Result<(), ComputeError> {
+        if let Some(uuid) = self.uuid {
+            self.compute_client.delete_pool(uuid).await
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::resources::TaskResources;
+    use crate::compute::test_utils::compute_client;
+    use crate::storage::LocalStorageClient;
+
+    #[test]
+    fn unknown_states_keep_waiting() {
+        let state = PoolState::from("Hibernating");
+        assert_eq!(state, PoolState::Unknown(String::from("Hibernating")));
+        assert!(!state.is_open() && !state.is_closed_or_failed());
+        assert!(PoolState::from("Failure").is_closed_or_failed());
+    }
+
+    #[test]
+    fn tasks_need_a_submitted_pool() {
+        let compute_client = compute_client();
+        let mut pool = Pool::new(&compute_client, "pool", "docker-batch", None, 2);
+        assert!(matches!(
+            pool.create_task("task", None, 1.into()),
+            Err(ComputeError::NotSubmitted)
+        ));
+        let uuid = uuid::Uuid::new_v4();
+        pool.uuid = Some(uuid);
+        let task = pool.create_task("task", None, 1.into()).unwrap();
+        assert_eq!(task.pool_uuid, Some(uuid));
+    }
+
+    #[tokio::test]
+    async fn pool_tasks_upload_their_resources() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = LocalStorageClient::new(root.path().join("storage"));
+        std::fs::write(root.path().join("run.sh"), "render").unwrap();
+        let mut server = mockito::Server::new_async().await;
+        let uuid = uuid::Uuid::new_v4();
+        let post = server
+            .mock("POST", "/v1/tasks")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "advancedResourceBuckets": [{"bucketName": "inputs"}],
+            })))
+            .with_body(serde_json::json!({ "uuid": uuid }).to_string())
+            .create_async()
+            .await;
+        let compute_client = ComputeClient::new(server.url(), String::from("v1"), "key").unwrap();
+        let mut pool = Pool::new(&compute_client, "pool", "docker-batch", None, 2);
+        pool.set_storage(Some(&storage));
+        pool.uuid = Some(uuid::Uuid::new_v4());
+
+        let mut task = pool.create_task("task", None, 1.into()).unwrap();
+        let mut resources = TaskResources::new("inputs", None);
+        resources.add_file(root.path().join("run.sh"), "run.sh");
+        task.input_resources.push(resources);
+        task.run().await.unwrap();
+
+        post.assert_async().await;
+        assert_eq!(task.uuid, Some(uuid));
+        assert_eq!(
+            storage.list_objects("inputs").await.unwrap()[0].key,
+            "run.sh"
+        );
+    }
+}
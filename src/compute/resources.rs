@@ -0,0 +1,218 @@
+use crate::compute::models::resources_bucket::{
+    PrefixFiltering, ResourcesBucket, ResourcesFiltering, ResourcesTransformation, StripPrefix,
+};
+use crate::storage::sync::{normalize_prefix, sync_file_to_remote, sync_local_to_remote};
+use crate::storage::{StorageClient, StorageError, SyncOptions, SyncReport};
+
+use std::path::PathBuf;
+
+/// Local file or directory to upload in a resource bucket
+pub enum ResourceInput {
+    /// Single file, uploaded to `key`
+    File { local_path: PathBuf, key: String },
+    /// Directory, uploaded recursively under `key_prefix`
+    Directory {
+        local_path: PathBuf,
+        key_prefix: String,
+    },
+}
+
+/// Local inputs of a task, uploaded to a resource bucket by `Task::run()`
+///
+/// Keys are relative to `prefix` when one is set. Only the objects under
+/// `prefix` are then made available to the task, and `strip_prefix` makes
+/// them appear at the root of the task working directory.
+/// Objects already up to date in the bucket are not uploaded again.
+pub struct TaskResources {
+    /// Name of the bucket, created if it does not exist
+    pub bucket: String,
+    /// Prefix of the uploaded keys, filtering the resources of the task
+    pub prefix: Option<String>,
+    /// Whether to remove `prefix` from the resource paths seen by the task
+    pub strip_prefix: bool,
+    /// Time to live of the resources in the compute nodes cache
+    pub cache_ttl_sec: Option<u32>,
+    /// Files and directories to upload
+    pub inputs: Vec<ResourceInput>,
+}
+
+impl TaskResources {
+    /// # Arguments
+    /// * `bucket` - Name of the resource bucket
+    /// * `prefix` - Prefix of the uploaded keys, `None` for the bucket root
+    pub fn new(bucket: &str, prefix: Option<&str>) -> Self {
+        Self {
+            bucket: bucket.to_owned(),
+            prefix: prefix.map(normalize_prefix).filter(|p| !p.is_empty()),
+            strip_prefix: false,
+            cache_ttl_sec: None,
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Upload the local file `local_path` to `key`
+    pub fn add_file(&mut self, local_path: impl Into<PathBuf>, key: &str) -> &mut Self {
+        self.inputs.push(ResourceInput::File {
+            local_path: local_path.into(),
+            key: key.to_owned(),
+        });
+        self
+    }
+
+    /// Upload the content of the local directory `local_path` under `key_prefix`
+    pub fn add_directory(&mut self, local_path: impl Into<PathBuf>, key_prefix: &str) -> &mut Self {
+        self.inputs.push(ResourceInput::Directory {
+            local_path: local_path.into(),
+            key_prefix: key_prefix.to_owned(),
+        });
+        self
+    }
+
+    /// Resource bucket to give to the task
+    pub fn resources_bucket(&self) -> ResourcesBucket {
+        ResourcesBucket {
+            bucket_name: Some(self.bucket.clone()),
+            filtering: ResourcesFiltering {
+                prefix_filtering: PrefixFiltering {
+                    prefix: self.prefix.clone(),
+                },
+            },
+            resources_transformation: ResourcesTransformation {
+                strip_prefix: StripPrefix {
+                    prefix: self.prefix.clone().filter(|_| self.strip_prefix),
+                },
+            },
+            cache_ttl_sec: self.cache_ttl_sec,
+        }
+    }
+
+    /// Create the bucket if needed and upload the missing or outdated inputs
+    ///
+    /// # Errors
+    /// The first transfer failure, after every input has been attempted
+    pub async fn upload<S: StorageClient + ?Sized>(
+        &self,
+        storage: &S,
+    ) -> Result<SyncReport, StorageError> {
+        if !storage.buckets().await?.contains(&self.bucket) {
+            storage.create_bucket(&self.bucket).await?;
+        }
+        let prefix = self.prefix.as_deref().unwrap_or_default();
+        let options = SyncOptions::default();
+        let mut report = SyncReport::default();
+        for input in &self.inputs {
+            match input {
+                ResourceInput::File { local_path, key } => {
+                    let key = format!("{prefix}{}", key.trim_start_matches('/'));
+                    match sync_file_to_remote(storage, &self.bucket, local_path, &key).await {
+                        Ok(Some(size)) => report.add_transfer(key, Ok(size)),
+                        Ok(None) => report.skipped.push(key),
+                        Err(e) => report.add_transfer(key, Err(e)),
+                    }
+                }
+                ResourceInput::Directory {
+                    local_path,
+                    key_prefix,
+                } => {
+                    let key_prefix = normalize_prefix(&format!(
+                        "{prefix}{}",
+                        key_prefix.trim_start_matches('/')
+                    ));
+                    let res = sync_local_to_remote(
+                        storage,
+                        &self.bucket,
+                        local_path,
+                        &key_prefix,
+                        &options,
+                    )
+                    .await;
+                    match res {
+                        Ok(dir_report) => report.merge(dir_report, &key_prefix),
+                        Err(e) => report.add_transfer(key_prefix, Err(e)),
+                    }
+                }
+            }
+        }
+        let mut report = report.sorted();
+        if report.failed.is_empty() {
+            Ok(report)
+        } else {
+            Err(report.failed.swap_remove(0).1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::LocalStorageClient;
+
+    #[tokio::test]
+    async fn upload_inputs_once() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = LocalStorageClient::new(root.path().join("storage"));
+        let input = root.path().join("input");
+        std::fs::create_dir_all(input.join("scenes")).unwrap();
+        std::fs::write(input.join("main.blend"), "main").unwrap();
+        std::fs::write(input.join("scenes/a.blend"), "a").unwrap();
+        std::fs::write(root.path().join("run.sh"), "render").unwrap();
+
+        let mut resources = TaskResources::new("inputs", Some("job-1"));
+        resources.strip_prefix = true;
+        resources
+            .add_file(root.path().join("run.sh"), "run.sh")
+            .add_directory(&input, "data");
+        let report = resources.upload(&storage).await.unwrap();
+        assert_eq!(
+            report.transferred,
+            vec![
+                "job-1/data/main.blend",
+                "job-1/data/scenes/a.blend",
+                "job-1/run.sh"
+            ]
+        );
+        let report = resources.upload(&storage).await.unwrap();
+        assert!(report.transferred.is_empty());
+        assert_eq!(report.skipped.len(), 3);
+
+        let bucket = resources.resources_bucket();
+        assert_eq!(bucket.bucket_name.as_deref(), Some("inputs"));
+        assert_eq!(
+            bucket.filtering.prefix_filtering.prefix.as_deref(),
+            Some("job-1/")
+        );
+        assert_eq!(
+            bucket
+                .resources_transformation
+                .strip_prefix
+                .prefix
+                .as_deref(),
+            Some("job-1/")
+        );
+
+        resources.add_file(root.path().join("missing"), "missing");
+        assert!(matches!(
+            resources.upload(&storage).await,
+            Err(StorageError::LocalFileDoesNotExist)
+        ));
+    }
+
+    #[tokio::test]
+    async fn leading_slashes_are_ignored() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = LocalStorageClient::new(root.path().join("storage"));
+        let input = root.path().join("input");
+        std::fs::create_dir_all(&input).unwrap();
+        std::fs::write(input.join("main.blend"), "main").unwrap();
+
+        let mut resources = TaskResources::new("inputs", Some("job-1"));
+        resources
+            .add_file(input.join("main.blend"), "/main.blend")
+            .add_directory(&input, "/data");
+        let report = resources.upload(&storage).await.unwrap();
+        assert_eq!(
+            report.transferred,
+            vec!["job-1/data/main.blend", "job-1/main.blend"]
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use crate::compute::ComputeError;
+
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// Retry policy applied by the [`ComputeClient`](crate::compute::client::ComputeClient)
+/// to its requests.
+///
+/// Transport failures, 5xx and 429 responses are retried with an exponential
+/// backoff. A `Retry-After` header sent by the API takes precedence over the
+/// computed backoff.
+/// Non idempotent requests (task/pool/job creation...) are only retried when
+/// the API guarantees they were not processed (429, connection failure).
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled at each attempt
+    pub base_delay: Duration,
+    /// Upper bound of the delay between two attempts
+    pub max_delay: Duration,
+    /// Fraction of the delay that is randomized, between 0 and 1
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy sending every request exactly once
+    pub const fn no_retry() -> Self {
+        Self {
+            max_attempts: 1,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            jitter: 0.0,
+        }
+    }
+
+    /// Whether a failed attempt should be retried
+    ///
+    /// # Arguments
+    /// * `error` - The error of the failed attempt
+    /// * `idempotent` - Whether the request can safely be sent several times
+    pub fn should_retry(&self, error: &ComputeError, idempotent: bool) -> bool {
+        match error {
+            ComputeError::RateLimited(_) => true,
+            ComputeError::Transport { source, .. } => idempotent || source.is_connect(),
+            ComputeError::Api(e) => idempotent && e.status.is_server_error(),
+            _ => false,
+        }
+    }
+
+    /// Delay to wait before the next attempt
+    ///
+    /// # Arguments
+    /// * `attempt` - Number of the attempt that just failed, starting at 1
+    /// * `error` - The error of the failed attempt
+    pub fn delay(&self, attempt: u32, error: &ComputeError) -> Duration {
+        if let Some(retry_after) = error.api_error().and_then(|e| e.retry_after) {
+            return retry_after.min(self.max_delay);
+        }
+        self.backoff(attempt)
+    }
+
+    /// Exponential backoff delay, with jitter, after the given attempt
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2_u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        delay.mul_f64(1.0 - jitter * fastrand::f64())
+    }
+}
+
+/// Parse the value of a `Retry-After` header, either a number of seconds
+/// or an HTTP date
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&Utc) - Utc::now())
+        .to_std()
+        .ok()
+        .or(Some(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(10),
+            jitter: 0.0,
+        };
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(5), Duration::from_secs(10));
+        assert_eq!(policy.backoff(64), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn backoff_jitter_stays_in_bounds() {
+        let policy = RetryPolicy {
+            jitter: 0.5,
+            ..RetryPolicy::default()
+        };
+        for _ in 0..100 {
+            let delay = policy.backoff(2);
+            assert!(delay <= Duration::from_secs(1));
+            assert!(delay >= Duration::from_millis(500));
+        }
+    }
+
+    #[test]
+    fn retry_after_parsing() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        let in_a_minute = (Utc::now() + chrono::TimeDelta::seconds(60)).to_rfc2822();
+        let delay = parse_retry_after(&in_a_minute).unwrap();
+        assert!(delay > Duration::from_secs(50) && delay <= Duration::from_secs(60));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+}
@@ -0,0 +1,46 @@
+use crate::compute::client::ComputeClient;
+use crate::compute::ComputeError;
+
+/// High level wrapper around the secrets store of the account
+/// This is returned by QarnotClient::secrets()
+/// Secrets are identified by their full path, such as `db/prod/password`,
+/// and are made available to tasks through their `secrets_access_rights`.
+pub struct Secrets<'a> {
+    compute_client: &'a ComputeClient,
+}
+
+impl<'a> Secrets<'a> {
+    pub const fn new(compute_client: &'a ComputeClient) -> Self {
+        Self { compute_client }
+    }
+
+    /// Create the secret `key`
+    pub async fn create(&self, key: &str, value: &str) -> Result<(), ComputeError> {
+        self.compute_client.put_secret(key, value).await
+    }
+
+    /// Get the value of the secret `key`
+    pub async fn get(&self, key: &str) -> Result<String, ComputeError> {
+        self.compute_client.get_secret(key).await
+    }
+
+    /// Replace the value of the existing secret `key`
+    pub async fn update(&self, key: &str, value: &str) -> Result<(), ComputeError> {
+        self.compute_client.patch_secret(key, value).await
+    }
+
+    /// Delete the secret `key`
+    pub async fn delete(&self, key: &str) -> Result<(), ComputeError> {
+        self.compute_client.delete_secret(key).await
+    }
+
+    /// Secrets and sub-directories (ending with `/`) directly under `prefix`
+    pub async fn list(&self, prefix: &str) -> Result<Vec<String>, ComputeError> {
+        self.compute_client.get_secrets(prefix, false).await
+    }
+
+    /// Every secret starting with `prefix`, sub-directories included
+    pub async fn list_recursive(&self, prefix: &str) -> Result<Vec<String>, ComputeError> {
+        self.compute_client.get_secrets(prefix, true).await
+    }
+}
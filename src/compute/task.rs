@@ -1,14 +1,30 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::Path;
 use std::str::FromStr;
+use std::time::Duration;
 
+use crate::client::Error;
 use crate::compute::client::ComputeClient;
+use crate::compute::events::{TaskEvent, TaskSnapshot};
+use crate::compute::models::instance_range::ParseInstanceRangeError;
 use crate::compute::models::{
-    CompletedFrameOutput, Constants, HardwareConstraintVariant, Privileges, QError,
-    QTaskStatusOutput, ResourcesBucket, RetrySettings, TaskCreationInput, TaskOutput,
-    TaskUpdateInput,
+    CompletedFrameOutput, Constants, DependencyInput, ForcedConstant, ForcedConstantAccess,
+    HardwareConstraintVariant, InstanceRange, PeriodicSnapshot, Privileges, QError,
+    QTaskStatusOutput, ResourcesBucket, RetrySettings, SchedulingClass, SecretsAccessRights,
+    StateTransition, TaskCloneInput, TaskCreationInput, TaskOutput, TaskRedoInput, TaskUpdateInput,
+    UniqueSnapshot,
 };
+use crate::compute::resources::TaskResources;
+use crate::compute::task_builder::{self, ValidationError};
 use crate::compute::ComputeError;
+use crate::storage::sync::{normalize_prefix, sync_remote_to_local, sync_remote_to_local_filtered};
+use crate::storage::{StorageClient, SyncOptions, SyncReport};
 
 use chrono::{DateTime, NaiveTime, TimeDelta, Utc};
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
+use std::future::Future;
+pub use tokio_util::sync::CancellationToken;
 
 pub enum ProfileOrPool {
     Profile(String),
@@ -29,7 +45,7 @@ impl From<uuid::Uuid> for ProfileOrPool {
 
 pub enum InstancesOrRange {
     InstanceCount(i32),
-    Range(String),
+    Range(InstanceRange),
 }
 
 impl From<i32> for InstancesOrRange {
@@ -38,59 +54,76 @@ impl From<i32> for InstancesOrRange {
     }
 }
 
-impl From<&str> for InstancesOrRange {
-    fn from(range: &str) -> Self {
-        Self::Range(range.to_owned())
+impl From<InstanceRange> for InstancesOrRange {
+    fn from(range: InstanceRange) -> Self {
+        Self::Range(range)
     }
 }
 
-pub enum State {
-    Submitted,
-    PartiallyDispatched,
-    FullyDispatched,
-    PartiallyExecuting,
-    FullyExecuting,
-    DownloadingResults,
-    UploadingResults,
+impl TryFrom<&str> for InstancesOrRange {
+    type Error = ParseInstanceRangeError;
+
+    fn try_from(range: &str) -> Result<Self, Self::Error> {
+        Ok(Self::Range(range.parse()?))
+    }
+}
+
+/// Task states, kept here for compatibility
+pub use crate::compute::models::TaskState as State;
+
+/// How waiting for a task ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaitOutcome {
+    /// The task finished (Success/Cancelled/Failure)
+    Finished,
+    /// The deadline was reached before the task finished
+    TimedOut,
+    /// The wait was cancelled before the task finished
     Cancelled,
-    Success,
-    Failure,
-    PendingDelete,
-    PendingCancel,
 }
 
-impl From<&str> for State {
-    fn from(state: &str) -> Self {
-        match state {
-            "Submitted" => Self::Submitted,
-            "PartiallyDispatched" => Self::PartiallyDispatched,
-            "FullyDispatched" => Self::FullyDispatched,
-            "PartiallyExecuting" => Self::PartiallyExecuting,
-            "FullyExecuting" => Self::FullyExecuting,
-            "DownloadingResults" => Self::DownloadingResults,
-            "UploadingResults" => Self::UploadingResults,
-            "Cancelled" => Self::Cancelled,
-            "Success" => Self::Success,
-            "Failure" => Self::Failure,
-            "PendingDelete" => Self::PendingDelete,
-            "PendingCancel" => Self::PendingCancel,
-            _ => Self::Failure,
-        }
-    }
+/// How [`Task::retry_failed`] runs the failed instances again
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetryStrategy {
+    /// Redo the task with `POST /tasks/{uuid}/retry`
+    Retry,
+    /// Redo the task with `POST /tasks/{uuid}/recover`
+    Recover,
+    /// Redo the task with `POST /tasks/{uuid}/resume`
+    Resume,
+    /// Submit a new task from the wrapper fields, with its instances
+    /// restricted to the failed ones
+    Resubmit,
 }
 
-impl State {
-    pub const fn is_running_or_downloading(&self) -> bool {
-        matches!(
-            self,
-            Self::Submitted
-                | Self::PartiallyDispatched
-                | Self::FullyDispatched
-                | Self::PartiallyExecuting
-                | Self::FullyExecuting
-                | Self::DownloadingResults
-                | Self::UploadingResults
-        )
+/// Selection of the results to fetch with [`Task::download_results`]
+pub struct DownloadResultsOptions {
+    /// Only download the results of these instance ids, as listed in
+    /// `completed_instances`
+    pub instances: Option<Vec<i32>>,
+    /// Glob the whole result path must match, e.g. `*/*.png`
+    /// Paths are relative to `prefix`, and `*` also matches `/`.
+    pub glob: Option<glob::Pattern>,
+    /// Regex searched anywhere in the result path relative to `prefix`,
+    /// anchor it with `^` and `$` to match the whole path. When both `glob`
+    /// and `regex` are set, a result is downloaded only if it matches both.
+    pub regex: Option<regex::Regex>,
+    /// Prefix of the results in the result bucket, defaults to the
+    /// `results_bucket_prefix` of the task
+    pub prefix: Option<String>,
+    /// Maximum number of simultaneous downloads
+    pub concurrency: usize,
+}
+
+impl Default for DownloadResultsOptions {
+    fn default() -> Self {
+        Self {
+            instances: None,
+            glob: None,
+            regex: None,
+            prefix: None,
+            concurrency: SyncOptions::default().concurrency,
+        }
     }
 }
 
@@ -102,30 +135,48 @@ impl State {
 /// fields are updated when running run, wait, etc.
 pub struct Task<'a> {
     compute_client: &'a ComputeClient,
+    storage: Option<&'a dyn StorageClient>,
     pub name: String,
     pub shortname: Option<String>,
     pub profile: Option<String>,
     pub pool_uuid: Option<uuid::Uuid>,
     pub job_uuid: Option<uuid::Uuid>,
     pub instance_count: Option<i32>,
-    pub advanced_range: Option<String>,
+    pub advanced_range: Option<InstanceRange>,
     pub running_core_count: u32,
     pub running_instance_count: u32,
     pub resouce_buckets: Option<Vec<String>>,
     pub advanced_resource_buckets: Option<Vec<ResourcesBucket>>,
+    /// Local inputs uploaded by `run()` and added to the resource buckets
+    pub input_resources: Vec<TaskResources>,
     pub result_bucket: Option<String>,
+    /// Prefix of the results in `result_bucket`
+    pub results_bucket_prefix: Option<String>,
+    pub snapshot_bucket: Option<String>,
+    pub snapshot_bucket_prefix: Option<String>,
     pub constants: Option<Constants>,
+    /// Constants overriding the profile ones, requires specific permissions
+    pub forced_constants: Option<Vec<ForcedConstant>>,
+    pub secrets_access_rights: Option<SecretsAccessRights>,
+    /// Priority of the task (can be set only by Admin users)
+    pub priority: Option<i32>,
+    pub scheduling_type: Option<SchedulingClass>,
+    /// Key of the reserved machine to run on, with the reserved scheduling type
+    pub targeted_reserved_machine_key: Option<String>,
     pub dependent_on: Option<Vec<uuid::Uuid>>,
     pub auto_update: bool,
     pub last_auto_update_state: bool,
     pub update_cache_time: TimeDelta,
     pub last_cache: DateTime<Utc>,
+    /// Delay between two updates when waiting for or watching the task
+    pub poll_interval: Duration,
     pub constraints: Option<Constants>,
     pub labels: Option<std::collections::HashMap<String, String>>,
     pub state: Option<State>,
     pub uuid: Option<uuid::Uuid>,
     //pub snapshots: ???
-    pub dirty: bool,
+    /// Field values as last submitted to or received from the API
+    committed: Option<HashMap<&'static str, serde_json::Value>>,
     pub rescount: i32,
     pub snapshot_whitelist: Option<String>,
     pub snapshot_blacklist: Option<String>,
@@ -136,10 +187,13 @@ pub struct Task<'a> {
     pub tags: Option<Vec<String>>,
     pub errors: Option<Vec<QError>>,
     pub is_summary: bool,
-    pub completion_time_to_live: Option<DateTime<Utc>>,
+    /// Life time of the task once completed
+    pub completion_time_to_live: Option<TimeDelta>,
     pub auto_delete: bool,
     pub wait_for_pool_resources_synchronization: Option<bool>,
-    pub previous_state: Option<String>,
+    pub previous_state: Option<State>,
+    /// Known state transitions of the task, oldest first
+    pub state_history: Vec<StateTransition>,
     pub last_modified: Option<DateTime<Utc>>,
     pub snapshot_interval: Option<u32>,
     pub progress: Option<f32>,
@@ -154,6 +208,12 @@ pub struct Task<'a> {
 }
 
 impl<'a> Task<'a> {
+    /// Set the storage client used to fetch the task data
+    /// This is done by QarnotClient::create_task()
+    pub fn set_storage(&mut self, storage: Option<&'a dyn StorageClient>) {
+        self.storage = storage;
+    }
+
     pub fn new(
         compute_client: &'a ComputeClient,
         name: &str,
@@ -161,43 +221,54 @@ impl<'a> Task<'a> {
         shortname: Option<String>,
         instance_range: InstancesOrRange,
     ) -> Self {
-        let mut instance_count: Option<i32> = None;
-        let mut advanced_range: Option<String> = None;
-        let mut profile: Option<String> = None;
-        let mut pool_uuid: Option<uuid::Uuid> = None;
-        match instance_range {
-            InstancesOrRange::InstanceCount(n) => instance_count = Some(n),
-            InstancesOrRange::Range(s) => advanced_range = Some(s),
-        }
+        let mut task = Self::blank(compute_client, name);
+        task.shortname = shortname;
+        task.set_instances(instance_range);
         match profile_pool {
-            ProfileOrPool::Pool(p) => pool_uuid = Some(p),
-            ProfileOrPool::Profile(p) => profile = Some(p),
+            ProfileOrPool::Pool(p) => task.pool_uuid = Some(p),
+            ProfileOrPool::Profile(p) => task.profile = Some(p),
         }
+        task
+    }
+
+    /// Task without profile, pool nor instances
+    pub(crate) fn blank(compute_client: &'a ComputeClient, name: &str) -> Self {
         Self {
             compute_client,
+            storage: None,
             name: name.to_owned(),
-            shortname,
-            profile,
-            pool_uuid,
+            shortname: None,
+            profile: None,
+            pool_uuid: None,
             job_uuid: None,
-            instance_count,
-            advanced_range,
+            instance_count: None,
+            advanced_range: None,
             running_core_count: 0,
             running_instance_count: 0,
             resouce_buckets: None,
             advanced_resource_buckets: None,
+            input_resources: Vec::new(),
             result_bucket: None,
+            results_bucket_prefix: None,
+            snapshot_bucket: None,
+            snapshot_bucket_prefix: None,
             constants: None,
+            forced_constants: None,
+            secrets_access_rights: None,
+            priority: None,
+            scheduling_type: None,
+            targeted_reserved_machine_key: None,
             dependent_on: None,
             auto_update: false,
             last_auto_update_state: false,
             update_cache_time: TimeDelta::seconds(5),
             last_cache: Utc::now(),
+            poll_interval: Duration::from_secs(5),
             constraints: None,
             labels: None,
             state: None,
             uuid: None,
-            dirty: false,
+            committed: None,
             rescount: 0,
             snapshot_whitelist: None,
             snapshot_blacklist: None,
@@ -212,6 +283,7 @@ impl<'a> Task<'a> {
             auto_delete: false,
             wait_for_pool_resources_synchronization: None,
             previous_state: None,
+            state_history: Vec::new(),
             last_modified: None,
             snapshot_interval: None,
             progress: None,
@@ -226,8 +298,171 @@ impl<'a> Task<'a> {
         }
     }
 
+    /// Set the instance count or the advanced range of the task
+    pub(crate) fn set_instances(&mut self, instances: InstancesOrRange) {
+        match instances {
+            InstancesOrRange::InstanceCount(n) => self.instance_count = Some(n),
+            InstancesOrRange::Range(s) => self.advanced_range = Some(s),
+        }
+    }
+
+    /// Attach local files or directories to the task
+    /// They are uploaded when running the task
+    pub fn add_resources(&mut self, resources: TaskResources) {
+        self.input_resources.push(resources);
+    }
+
+    /// Give the task access to the secret `key`
+    pub fn grant_secret(&mut self, key: &str) {
+        self.secrets_access_rights
+            .get_or_insert_with(SecretsAccessRights::default)
+            .add_secret(key);
+    }
+
+    /// Give the task access to every secret starting with `prefix`
+    pub fn grant_secrets_prefix(&mut self, prefix: &str) {
+        self.secrets_access_rights
+            .get_or_insert_with(SecretsAccessRights::default)
+            .add_prefix(prefix);
+    }
+
+    /// Override a constant of the profile, replacing a previous override of it
+    /// This is meant for development and requires specific permissions.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the constant, declared by the profile
+    /// * `value` - Value of the constant
+    /// * `access` - Whether the task can change the constant
+    /// * `export_env` - Whether the constant is exported to the environment
+    pub fn force_constant(
+        &mut self,
+        name: &str,
+        value: &str,
+        access: ForcedConstantAccess,
+        export_env: bool,
+    ) {
+        let forced = self.forced_constants.get_or_insert_with(Vec::new);
+        forced.retain(|c| c.constant_name.as_deref() != Some(name));
+        forced.push(ForcedConstant {
+            constant_name: Some(name.to_owned()),
+            forced_value: Some(value.to_owned()),
+            force_export_in_environment: Some(export_env),
+            access: Some(access),
+        });
+    }
+
+    /// Check the forced constants against the constants declared by the profile
+    /// Tasks in a pool or a job use the profile of the pool. Nothing is checked
+    /// when no profile is found, e.g. for a job without pool.
+    ///
+    /// # Errors
+    /// * `Error::Validation(_)` - A forced constant is not declared by the profile
+    /// * `Error::Compute(_)` - The pool, job or profile could not be fetched
+    pub async fn check_forced_constants(&self) -> Result<(), Error> {
+        if self.forced_constants.as_ref().is_none_or(Vec::is_empty) {
+            return Ok(());
+        }
+        let Some(profile) = self.resolve_profile().await? else {
+            warn!(
+                "No profile found for task {}, forced constants not checked",
+                self.name
+            );
+            return Ok(());
+        };
+        let details = self.compute_client.get_profile_details(&profile).await?;
+        let violations = task_builder::check_forced_constants(self, &details);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            let e = ValidationError { violations };
+            error!("{}", e);
+            Err(Error::Validation(e))
+        }
+    }
+
+    /// Profile the task runs with: its own, or the one of its pool, directly
+    /// or through its job
+    async fn resolve_profile(&self) -> Result<Option<String>, ComputeError> {
+        if let Some(profile) = &self.profile {
+            return Ok(Some(profile.clone()));
+        }
+        let pool_uuid = match (self.pool_uuid, self.job_uuid) {
+            (Some(pool_uuid), _) => Some(pool_uuid),
+            (None, Some(job_uuid)) => self.compute_client.get_job_info(job_uuid).await?.pool_uuid,
+            (None, None) => None,
+        };
+        match pool_uuid {
+            Some(pool_uuid) => Ok(self.compute_client.get_pool_info(pool_uuid).await?.profile),
+            None => Ok(None),
+        }
+    }
+
+    /// Upload the input resources, returning the resource buckets of the task
+    ///
+    /// Advanced resource buckets replace the plain ones, so the plain
+    /// buckets are converted as soon as input resources are used.
+    async fn stage_resources(
+        &self,
+    ) -> Result<(Option<Vec<String>>, Option<Vec<ResourcesBucket>>), Error> {
+        if self.input_resources.is_empty() {
+            return Ok((
+                self.resouce_buckets.clone(),
+                self.advanced_resource_buckets.clone(),
+            ));
+        }
+        let storage = self.storage.ok_or(Error::NoStorageClient)?;
+        let mut buckets = self.advanced_resource_buckets.clone().unwrap_or_default();
+        buckets.extend(
+            self.resouce_buckets
+                .iter()
+                .flatten()
+                .map(|name| ResourcesBucket {
+                    bucket_name: Some(name.clone()),
+                    ..ResourcesBucket::default()
+                }),
+        );
+        for resources in &self.input_resources {
+            let report = resources.upload(storage).await?;
+            debug!(
+                "Uploaded {} resources to {} ({} up to date)",
+                report.transferred.len(),
+                resources.bucket,
+                report.skipped.len()
+            );
+            buckets.push(resources.resources_bucket());
+        }
+        Ok((None, Some(buckets)))
+    }
+
+    /// Check the task against the API rules that do not depend on the account
+    ///
+    /// # Errors
+    /// Every broken rule, see [`task_builder::TaskViolation`]
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        task_builder::validate(self)
+    }
+
     /// Run task
-    pub async fn run(&mut self) -> Result<(), ComputeError> {
+    /// The task is validated, its forced constants checked against its
+    /// profile, then the input resources are uploaded before submitting it
+    ///
+    /// # Errors
+    /// * `Error::Validation` - The task breaks API rules, nothing was sent
+    /// * `Error::Storage` / `Error::NoStorageClient` - The input resources could not be uploaded
+    /// * `Error::Compute` - The profile lookup or the submission failed
+    pub async fn run(&mut self) -> Result<(), Error> {
+        self.validate().map_err(|e| {
+            error!("{}", e);
+            Error::Validation(e)
+        })?;
+        self.check_forced_constants().await?;
+        let (resource_buckets, advanced_resource_buckets) = self.stage_resources().await?;
+        // The results prefix is only supported along the new results bucket field
+        let (result_bucket, results_bucket) = if self.results_bucket_prefix.is_some() {
+            (None, self.result_bucket.clone())
+        } else {
+            (self.result_bucket.clone(), None)
+        };
         let input = TaskCreationInput {
             name: self.name.clone(),
             shortname: self.shortname.clone(),
@@ -235,34 +470,38 @@ impl<'a> Task<'a> {
             pool_uuid: self.pool_uuid,
             job_uuid: self.job_uuid,
             instance_count: self.instance_count,
-            advanced_ranges: self.advanced_range.clone(),
-            resource_buckets: self.resouce_buckets.clone(),
-            advanced_resource_buckets: self.advanced_resource_buckets.clone(),
-            result_bucket: self.result_bucket.clone(),
+            advanced_ranges: self.advanced_range.as_ref().map(ToString::to_string),
+            resource_buckets,
+            advanced_resource_buckets,
+            result_bucket,
             constants: self.constants.clone(),
-            forced_constants: None,
+            forced_constants: self.forced_constants.clone(),
             constraints: self.constraints.clone(),
             hardware_constraints: self.hardware_constraints.clone(),
-            secrets_access_rights: None,
+            secrets_access_rights: self.secrets_access_rights.clone(),
             tags: self.tags.clone(),
             snapshot_whitelist: self.snapshot_whitelist.clone(),
             snapshot_blacklist: self.snapshot_blacklist.clone(),
-            snapshot_bucket: None,
-            snapshot_bucket_prefix: None,
+            snapshot_bucket: self.snapshot_bucket.clone(),
+            snapshot_bucket_prefix: self.snapshot_bucket_prefix.clone(),
             results_whitelist: self.result_whitelist.clone(),
             results_blacklist: self.result_blacklist.clone(),
-            results_bucket: None,
-            results_bucket_prefix: None,
-            priority: None,
-            dependencies: None,
+            results_bucket,
+            results_bucket_prefix: self.results_bucket_prefix.clone(),
+            priority: self.priority,
+            dependencies: self.dependent_on.clone().map(|depends_on| DependencyInput {
+                depends_on: Some(depends_on),
+            }),
             auto_delete_on_completion: Some(self.auto_delete),
-            completion_time_to_live: None,
-            wait_for_pool_resources_synchronization: None,
+            completion_time_to_live: self.completion_time_to_live.map(format_time_span),
+            wait_for_pool_resources_synchronization: self.wait_for_pool_resources_synchronization,
             upload_results_on_cancellation: Some(self.upload_results_on_cancellation),
             labels: self.labels.clone(),
-            scheduling_type: None,
-            targeted_reserved_machine_key: None,
-            default_resources_cache_ttl_sec: None,
+            scheduling_type: self.scheduling_type,
+            targeted_reserved_machine_key: self.targeted_reserved_machine_key.clone(),
+            default_resources_cache_ttl_sec: self
+                .default_resources_cache_ttl_sec
+                .map(|ttl| i32::try_from(ttl).unwrap_or(i32::MAX)),
             privileges: self.privileges.clone(),
             retry_settings: self.retry_settings.clone(),
         };
@@ -271,45 +510,421 @@ impl<'a> Task<'a> {
         if self.shortname.is_none() {
             self.shortname = res.uuid.map(|u| u.to_string());
         };
+        self.committed = Some(self.field_values());
         Ok(())
     }
 
     /// Wait for task to finish
+    /// The task is updated every `poll_interval`
     pub async fn wait(&mut self) -> Result<(), ComputeError> {
-        if let Some(_uuid) = self.uuid {
-            while self.state.is_none()
-                || self
-                    .state
-                    .as_ref()
-                    .is_some_and(|s| s.is_running_or_downloading())
-            {
-                self.get_update(false).await?;
+        let mut events = std::pin::pin!(self.watch());
+        while let Some(event) = events.next().await {
+            event?;
+        }
+        Ok(())
+    }
+
+    /// Wait for task to finish, at most `timeout`
+    ///
+    /// # Arguments
+    /// * `timeout` - Maximum duration of the wait
+    /// * `abort_on_timeout` - Whether to abort the task if it is still running
+    pub async fn wait_with_timeout(
+        &mut self,
+        timeout: Duration,
+        abort_on_timeout: bool,
+    ) -> Result<WaitOutcome, ComputeError> {
+        let deadline = async {
+            tokio::time::sleep(timeout).await;
+            WaitOutcome::TimedOut
+        };
+        self.wait_until(deadline, abort_on_timeout).await
+    }
+
+    /// Wait for task to finish, or until `token` is cancelled
+    ///
+    /// # Arguments
+    /// * `token` - Token stopping the wait when cancelled
+    /// * `abort_on_cancel` - Whether to abort the task if it is still running
+    pub async fn wait_with_cancellation(
+        &mut self,
+        token: &CancellationToken,
+        abort_on_cancel: bool,
+    ) -> Result<WaitOutcome, ComputeError> {
+        let cancelled = async {
+            token.cancelled().await;
+            WaitOutcome::Cancelled
+        };
+        self.wait_until(cancelled, abort_on_cancel).await
+    }
+
+    /// Wait for task to finish or for `stop` to complete, whichever comes first
+    async fn wait_until(
+        &mut self,
+        stop: impl Future<Output = WaitOutcome>,
+        abort: bool,
+    ) -> Result<WaitOutcome, ComputeError> {
+        let outcome = tokio::select! {
+            biased;
+            outcome = stop => outcome,
+            res = self.wait() => res.map(|()| WaitOutcome::Finished)?,
+        };
+        if outcome != WaitOutcome::Finished {
+            warn!("Stopped waiting for task {:?}: {:?}", self.uuid, outcome);
+            if abort {
+                self.abort().await?;
             }
-            Ok(())
+        }
+        Ok(outcome)
+    }
+
+    /// Abort the task
+    pub async fn abort(&self) -> Result<(), ComputeError> {
+        if let Some(uuid) = self.uuid {
+            self.compute_client.post_abort_task(uuid).await
         } else {
+            Ok(())
+        }
+    }
+
+    /// Take a single snapshot of the running task
+    /// The snapshot uses the snapshot filters and bucket of the task.
+    ///
+    /// # Errors
+    /// * `ComputeError::NotSubmitted` - The task was not started
+    pub async fn snapshot_now(&self) -> Result<(), ComputeError> {
+        let Some(uuid) = self.uuid else {
+            error!("No uuid, have you started the task ?");
+            return Err(ComputeError::NotSubmitted);
+        };
+        let snapshot = UniqueSnapshot {
+            whitelist: self.snapshot_whitelist.clone(),
+            blacklist: self.snapshot_blacklist.clone(),
+            bucket: self.snapshot_bucket.clone(),
+            bucket_prefix: self.snapshot_bucket_prefix.clone(),
+        };
+        self.compute_client
+            .post_unique_snapshot(uuid, snapshot)
+            .await
+    }
+
+    /// Take snapshots of the running task every `interval`
+    /// The snapshots use the snapshot filters and bucket of the task.
+    ///
+    /// # Arguments
+    /// * `interval` - Delay between two snapshots, rounded to the second
+    /// # Errors
+    /// * `ComputeError::NotSubmitted` - The task was not started
+    pub async fn snapshot_every(&mut self, interval: Duration) -> Result<(), ComputeError> {
+        let Some(uuid) = self.uuid else {
+            error!("No uuid, have you started the task ?");
+            return Err(ComputeError::NotSubmitted);
+        };
+        let seconds = i32::try_from(interval.as_secs().max(1)).unwrap_or(i32::MAX);
+        let snapshot = PeriodicSnapshot {
+            interval: Some(seconds),
+            whitelist: self.snapshot_whitelist.clone(),
+            blacklist: self.snapshot_blacklist.clone(),
+            bucket: self.snapshot_bucket.clone(),
+            bucket_prefix: self.snapshot_bucket_prefix.clone(),
+        };
+        self.compute_client
+            .post_periodic_snapshot(uuid, snapshot)
+            .await?;
+        self.snapshot_interval = u32::try_from(seconds).ok();
+        Ok(())
+    }
+
+    /// Clone the task under a new name and track the clone
+    ///
+    /// Fields left to `None` in `overrides` keep the values of this task: the
+    /// result and snapshot buckets, constants and constraints are copied from
+    /// this wrapper, and the constants of `overrides` are merged over the
+    /// original ones. Resource buckets are kept by the API, including the
+    /// input resources uploaded when running this task.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the clone
+    /// * `overrides` - Fields to change in the clone
+    /// # Errors
+    /// * `Error::Compute(ComputeError::NotSubmitted)` - The task was not started
+    /// * `Error::Compute(ComputeError::MissingUuid(_))` - The API returned no clone uuid
+    /// * `Error::Compute(_)` - The task could not be cloned
+    pub async fn clone_as(&self, name: &str, overrides: TaskCloneInput) -> Result<Task<'a>, Error> {
+        let Some(uuid) = self.uuid else {
+            error!("No uuid, have you started the task ?");
+            return Err(ComputeError::NotSubmitted.into());
+        };
+        let res = self
+            .compute_client
+            .post_clone_task(uuid, self.clone_input(name, overrides))
+            .await?;
+        let Some(clone_uuid) = res.uuid else {
+            error!("No uuid returned when cloning task {}", uuid);
+            return Err(ComputeError::MissingUuid(format!("tasks/{uuid}/clone")).into());
+        };
+        debug!("Task {} cloned as {}", uuid, clone_uuid);
+        let mut clone = Task::blank(self.compute_client, name);
+        clone.storage = self.storage;
+        clone.update_cache_time = self.update_cache_time;
+        clone.poll_interval = self.poll_interval;
+        clone.uuid = Some(clone_uuid);
+        clone.get_update(true).await?;
+        Ok(clone)
+    }
+
+    /// Clone request keeping the fields of this task not set in `overrides`
+    fn clone_input(&self, name: &str, overrides: TaskCloneInput) -> TaskCloneInput {
+        let mut input = overrides;
+        input.name = Some(name.to_owned());
+        if let Some(constants) = &self.constants {
+            let mut merged = constants.clone();
+            merged
+                .0
+                .extend(input.constants.take().unwrap_or_default().0);
+            input.constants = Some(merged);
+        }
+        input.constraints = input.constraints.or_else(|| self.constraints.clone());
+        if input.result_bucket.is_none() && input.results_bucket.is_none() {
+            if self.results_bucket_prefix.is_some() {
+                input.results_bucket = self.result_bucket.clone();
+                input.results_bucket_prefix = input
+                    .results_bucket_prefix
+                    .or_else(|| self.results_bucket_prefix.clone());
+            } else {
+                input.result_bucket = self.result_bucket.clone();
+            }
+        }
+        if input.snapshot_bucket.is_none() {
+            input.snapshot_bucket = self.snapshot_bucket.clone();
+            input.snapshot_bucket_prefix = input
+                .snapshot_bucket_prefix
+                .or_else(|| self.snapshot_bucket_prefix.clone());
+        }
+        input
+    }
+
+    /// Instances in the `failed_range` of the last known status
+    ///
+    /// Cancelled instances (`cancelled_range`) are not included, and the range
+    /// is empty until the task status has been fetched with `get_update()`.
+    pub fn failed_instances(&self) -> InstanceRange {
+        self.status
+            .as_ref()
+            .and_then(|s| s.failed_range.clone())
+            .unwrap_or_default()
+    }
+
+    /// Run the failed instances of the task again
+    ///
+    /// The task is updated first, then nothing is done if no instance failed.
+    /// Otherwise the wrapper tracks the new task created by `strategy`, its
+    /// execution fields are reset until the next update.
+    ///
+    /// # Arguments
+    /// * `strategy` - How to create the new task, see [`RetryStrategy`]
+    /// # Errors
+    /// * `Error::Compute(ComputeError::NotSubmitted)` - The task was not started
+    /// * `Error::Compute(ComputeError::MissingUuid(_))` - The API returned no new task uuid
+    /// * `Error::Compute(_)` - The task could not be updated or redone
+    /// * Errors of [`Task::run`] for [`RetryStrategy::Resubmit`]
+    ///
+    /// Returns the instances of the new task, empty if none failed: the
+    /// failed ones for [`RetryStrategy::Resubmit`], and the ones reported by
+    /// the API for the other strategies, which choose the instances to redo.
+    pub async fn retry_failed(&mut self, strategy: RetryStrategy) -> Result<InstanceRange, Error> {
+        let Some(uuid) = self.uuid else {
             error!("No uuid, have you started the task ?");
-            Err(ComputeError::Generic)
+            return Err(ComputeError::NotSubmitted.into());
+        };
+        self.get_update(true).await?;
+        let failed = self.failed_instances();
+        if failed.is_empty() {
+            debug!("Task {} has no failed instance", uuid);
+            return Ok(failed);
         }
+        let input = TaskRedoInput::new();
+        let (redone, route) = match strategy {
+            RetryStrategy::Retry => (
+                self.compute_client.post_retry_task(uuid, input).await?,
+                "retry",
+            ),
+            RetryStrategy::Recover => (
+                self.compute_client.post_recover_task(uuid, input).await?,
+                "recover",
+            ),
+            RetryStrategy::Resume => (
+                self.compute_client.post_resume_task(uuid, input).await?,
+                "resume",
+            ),
+            RetryStrategy::Resubmit => {
+                self.resubmit(&failed).await?;
+                debug!("Task {} resubmitted as {:?}", uuid, self.uuid);
+                return Ok(failed);
+            }
+        };
+        let Some(new_uuid) = redone.uuid else {
+            error!("No uuid returned when redoing task {}", uuid);
+            return Err(ComputeError::MissingUuid(format!("tasks/{uuid}/{route}")).into());
+        };
+        debug!("Task {} redone as {}", uuid, new_uuid);
+        self.forget_execution();
+        self.uuid = Some(new_uuid);
+        self.get_update(true).await?;
+        Ok(self.instances())
+    }
+
+    /// Instances of the task, from its advanced range or its instance count
+    fn instances(&self) -> InstanceRange {
+        match (&self.advanced_range, self.instance_count) {
+            (Some(range), _) => range.clone(),
+            (None, Some(count)) if count > 0 => {
+                InstanceRange::from_bounds(0, u32::try_from(count - 1).unwrap_or_default())
+            }
+            _ => InstanceRange::new(),
+        }
+    }
+
+    /// Submit the task again, with only the `instances`
+    /// The wrapper is left unchanged if the submission fails.
+    async fn resubmit(&mut self, instances: &InstanceRange) -> Result<(), Error> {
+        let previous = (
+            self.uuid.take(),
+            self.shortname.take(),
+            self.instance_count.take(),
+            self.advanced_range.replace(instances.clone()),
+        );
+        if let Err(e) = self.run().await {
+            (
+                self.uuid,
+                self.shortname,
+                self.instance_count,
+                self.advanced_range,
+            ) = previous;
+            return Err(e);
+        }
+        self.forget_execution();
+        Ok(())
+    }
+
+    /// Reset the fields describing the execution of the tracked task
+    fn forget_execution(&mut self) {
+        self.state = None;
+        self.previous_state = None;
+        self.state_history.clear();
+        self.status = None;
+        self.completed_instances = None;
+        self.errors = None;
+        self.progress = None;
+        self.execution_time = None;
+        self.wall_time = None;
+        self.end_date = None;
+    }
+
+    /// Watch the task until it finishes
+    ///
+    /// The task is updated every `poll_interval` and the stream yields the
+    /// changes since the previous update. The first update reports the
+    /// current state of the task. The stream ends once the task is in a
+    /// terminal state (Success/Cancelled/Failure) or after the first error.
+    pub fn watch(&mut self) -> impl Stream<Item = Result<TaskEvent, ComputeError>> + use<'_, 'a> {
+        futures_util::stream::unfold(TaskWatch::new(self), |mut watch| async move {
+            loop {
+                if let Some(event) = watch.pending.pop_front() {
+                    return Some((Ok(event), watch));
+                }
+                if watch.done {
+                    return None;
+                }
+                watch.done = true;
+                if watch.task.uuid.is_none() {
+                    error!("No uuid, have you started the task ?");
+                    return Some((Err(ComputeError::NotSubmitted), watch));
+                }
+                if !watch.first {
+                    tokio::time::sleep(watch.task.poll_interval).await;
+                }
+                watch.first = false;
+                if let Err(e) = watch.task.get_update(true).await {
+                    return Some((Err(e), watch));
+                }
+                watch.observe();
+            }
+        })
     }
 
     /// Update current struct with values from a TaskOutput
+    /// Fields modified locally and not committed yet keep their local value,
+    /// they are still sent by the next `commit()`.
     fn update_fields(&mut self, updated_task: TaskOutput) {
+        let mut refresh = FieldRefresh {
+            dirty: self.dirty_fields(),
+            committed: self.committed.take().unwrap_or_else(|| self.field_values()),
+        };
         if let Some(name) = updated_task.name {
-            self.name = name;
-        }
-        self.shortname = updated_task.shortname;
-        self.profile = updated_task.profile;
-        self.pool_uuid = updated_task.pool_uuid;
-        self.job_uuid = updated_task.job_uuid;
-        self.instance_count = updated_task.instance_count;
-        self.advanced_range = updated_task.advanced_ranges;
-        self.wait_for_pool_resources_synchronization =
-            updated_task.wait_for_pool_resources_synchronization;
+            refresh.set("name", &mut self.name, name);
+        }
+        refresh.set("shortname", &mut self.shortname, updated_task.shortname);
+        refresh.set("profile", &mut self.profile, updated_task.profile);
+        refresh.set("pool_uuid", &mut self.pool_uuid, updated_task.pool_uuid);
+        refresh.set("job_uuid", &mut self.job_uuid, updated_task.job_uuid);
+        refresh.set(
+            "instance_count",
+            &mut self.instance_count,
+            updated_task.instance_count,
+        );
+        refresh.set(
+            "advanced_range",
+            &mut self.advanced_range,
+            updated_task
+                .advanced_ranges
+                .and_then(|r| r.parse().map_err(|e| warn!("{}", e)).ok()),
+        );
+        refresh.set(
+            "wait_for_pool_resources_synchronization",
+            &mut self.wait_for_pool_resources_synchronization,
+            updated_task.wait_for_pool_resources_synchronization,
+        );
         self.uuid = updated_task.uuid;
-        self.state = updated_task.state.map(|s| State::from(s.as_str()));
-        self.tags = updated_task.tags;
+        self.record_transition(
+            updated_task.previous_state.clone(),
+            updated_task.previous_state_transition_time.as_deref(),
+        );
+        self.record_transition(
+            updated_task.state.clone(),
+            updated_task.state_transition_time.as_deref(),
+        );
+        self.state = updated_task.state;
+        refresh.set("tags", &mut self.tags, updated_task.tags);
+        let constants = constants_value(updated_task.constants.as_ref());
+        refresh.set_as(
+            "constants",
+            &mut self.constants,
+            updated_task.constants,
+            constants,
+        );
+        refresh.set(
+            "snapshot_whitelist",
+            &mut self.snapshot_whitelist,
+            updated_task.snapshot_whitelist,
+        );
+        refresh.set(
+            "snapshot_blacklist",
+            &mut self.snapshot_blacklist,
+            updated_task.snapshot_blacklist,
+        );
+        refresh.set(
+            "retry_settings",
+            &mut self.retry_settings,
+            updated_task.retry_settings,
+        );
+        refresh.set("privileges", &mut self.privileges, updated_task.privileges);
         if let Some(upload_res) = updated_task.upload_results_on_cancellation {
-            self.upload_results_on_cancellation = upload_res;
+            refresh.set(
+                "upload_results_on_cancellation",
+                &mut self.upload_results_on_cancellation,
+                upload_res,
+            );
         }
         self.previous_state = updated_task.previous_state;
         self.last_modified = updated_task
@@ -325,8 +940,177 @@ impl<'a> Task<'a> {
         self.end_date = updated_task
             .end_date
             .map(|s| DateTime::<Utc>::from_str(s.as_str()).unwrap_or_default());
-        self.labels = updated_task.labels;
-        self.hardware_constraints = updated_task.hardware_constraints;
+        refresh.set("labels", &mut self.labels, updated_task.labels);
+        refresh.set(
+            "hardware_constraints",
+            &mut self.hardware_constraints,
+            updated_task.hardware_constraints,
+        );
+        refresh.set(
+            "result_bucket",
+            &mut self.result_bucket,
+            updated_task.result_bucket.or(updated_task.results_bucket),
+        );
+        refresh.set(
+            "results_bucket_prefix",
+            &mut self.results_bucket_prefix,
+            updated_task.results_bucket_prefix,
+        );
+        refresh.set(
+            "result_whitelist",
+            &mut self.result_whitelist,
+            updated_task.results_whitelist,
+        );
+        refresh.set(
+            "result_blacklist",
+            &mut self.result_blacklist,
+            updated_task.results_blacklist,
+        );
+        self.completed_instances = updated_task.completed_instances;
+        self.status = updated_task.status;
+        self.snapshot_interval = updated_task.snapshot_interval;
+        self.errors = updated_task.errors;
+        refresh.set(
+            "snapshot_bucket",
+            &mut self.snapshot_bucket,
+            updated_task.snapshot_bucket,
+        );
+        refresh.set(
+            "snapshot_bucket_prefix",
+            &mut self.snapshot_bucket_prefix,
+            updated_task.snapshot_bucket_prefix,
+        );
+        refresh.set(
+            "secrets_access_rights",
+            &mut self.secrets_access_rights,
+            updated_task.secrets_access_rights,
+        );
+        refresh.set(
+            "scheduling_type",
+            &mut self.scheduling_type,
+            updated_task.scheduling_type,
+        );
+        refresh.set(
+            "targeted_reserved_machine_key",
+            &mut self.targeted_reserved_machine_key,
+            updated_task.targeted_reserved_machine_key,
+        );
+        refresh.set(
+            "dependent_on",
+            &mut self.dependent_on,
+            updated_task.dependencies.and_then(|d| d.depends_on),
+        );
+        if let Some(auto_delete) = updated_task.auto_delete_on_completion {
+            refresh.set("auto_delete", &mut self.auto_delete, auto_delete);
+        }
+        let ttl = updated_task
+            .completion_time_to_live
+            .as_deref()
+            .and_then(parse_time_span);
+        refresh.set_as(
+            "completion_time_to_live",
+            &mut self.completion_time_to_live,
+            ttl,
+            serde_json::json!(ttl.map(format_time_span)),
+        );
+        self.committed = Some(refresh.committed);
+    }
+
+    /// Current values of the fields tracked by `commit()`
+    /// The API does not return `constraints` and `priority`, their committed
+    /// values are the ones last sent.
+    fn field_values(&self) -> HashMap<&'static str, serde_json::Value> {
+        use serde_json::json;
+        HashMap::from([
+            ("constants", constants_value(self.constants.as_ref())),
+            ("constraints", constants_value(self.constraints.as_ref())),
+            ("tags", json!(self.tags)),
+            ("labels", json!(self.labels)),
+            ("snapshot_whitelist", json!(self.snapshot_whitelist)),
+            ("snapshot_blacklist", json!(self.snapshot_blacklist)),
+            ("result_whitelist", json!(self.result_whitelist)),
+            ("result_blacklist", json!(self.result_blacklist)),
+            ("retry_settings", json!(self.retry_settings)),
+            (
+                "upload_results_on_cancellation",
+                json!(self.upload_results_on_cancellation),
+            ),
+            ("name", json!(self.name)),
+            ("shortname", json!(self.shortname)),
+            ("profile", json!(self.profile)),
+            ("pool_uuid", json!(self.pool_uuid)),
+            ("job_uuid", json!(self.job_uuid)),
+            ("instance_count", json!(self.instance_count)),
+            ("advanced_range", json!(self.advanced_range)),
+            ("resouce_buckets", json!(self.resouce_buckets)),
+            (
+                "advanced_resource_buckets",
+                json!(self.advanced_resource_buckets),
+            ),
+            ("result_bucket", json!(self.result_bucket)),
+            ("results_bucket_prefix", json!(self.results_bucket_prefix)),
+            ("hardware_constraints", json!(self.hardware_constraints)),
+            ("privileges", json!(self.privileges)),
+            ("snapshot_bucket", json!(self.snapshot_bucket)),
+            ("snapshot_bucket_prefix", json!(self.snapshot_bucket_prefix)),
+            ("forced_constants", json!(self.forced_constants)),
+            ("secrets_access_rights", json!(self.secrets_access_rights)),
+            ("priority", json!(self.priority)),
+            ("scheduling_type", json!(self.scheduling_type)),
+            (
+                "targeted_reserved_machine_key",
+                json!(self.targeted_reserved_machine_key),
+            ),
+            ("dependent_on", json!(self.dependent_on)),
+            ("auto_delete", json!(self.auto_delete)),
+            (
+                "completion_time_to_live",
+                json!(self.completion_time_to_live.map(format_time_span)),
+            ),
+            (
+                "default_resources_cache_ttl_sec",
+                json!(self.default_resources_cache_ttl_sec),
+            ),
+            (
+                "wait_for_pool_resources_synchronization",
+                json!(self.wait_for_pool_resources_synchronization),
+            ),
+        ])
+    }
+
+    /// Fields modified since the task was submitted or last updated
+    /// Always empty before the task is submitted.
+    pub fn dirty_fields(&self) -> Vec<&'static str> {
+        let Some(committed) = &self.committed else {
+            return Vec::new();
+        };
+        let mut fields: Vec<&'static str> = self
+            .field_values()
+            .into_iter()
+            .filter(|(field, value)| committed.get(field) != Some(value))
+            .map(|(field, _)| field)
+            .collect();
+        fields.sort_unstable();
+        fields
+    }
+
+    /// Whether fields were modified since the task was submitted or last updated
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty_fields().is_empty()
+    }
+
+    /// Add a transition to the state history unless it is already known
+    /// The API uses `0001-01-01T00:00:00Z` for transitions that did not happen.
+    fn record_transition(&mut self, state: Option<State>, time: Option<&str>) {
+        let time = time.and_then(|t| DateTime::<Utc>::from_str(t).ok());
+        let (Some(state), Some(time)) = (state, time.filter(|t| t.timestamp() > 0)) else {
+            return;
+        };
+        let transition = StateTransition { state, time };
+        if !self.state_history.contains(&transition) {
+            self.state_history.push(transition);
+            self.state_history.sort_by_key(|t| t.time);
+        }
     }
 
     /// Update struct with changes from the API
@@ -341,19 +1125,69 @@ impl<'a> Task<'a> {
         Ok(())
     }
 
+    /// Build the update of the modified fields, `None` if nothing changed
+    /// Fields reset to `None` are sent empty to clear them.
+    fn update_input(&self) -> Result<Option<TaskUpdateInput>, Error> {
+        let dirty = self.dirty_fields();
+        if dirty.is_empty() {
+            return Ok(None);
+        }
+        let mut input = TaskUpdateInput::default();
+        for field in dirty {
+            match field {
+                "constants" => input.constants = Some(self.constants.clone().unwrap_or_default()),
+                "constraints" => {
+                    input.constraints = Some(self.constraints.clone().unwrap_or_default());
+                }
+                "tags" => input.tags = Some(self.tags.clone().unwrap_or_default()),
+                "labels" => input.labels = Some(self.labels.clone().unwrap_or_default()),
+                "snapshot_whitelist" => {
+                    input.snapshot_whitelist =
+                        Some(self.snapshot_whitelist.clone().unwrap_or_default());
+                }
+                "snapshot_blacklist" => {
+                    input.snapshot_blacklist =
+                        Some(self.snapshot_blacklist.clone().unwrap_or_default());
+                }
+                "result_whitelist" => {
+                    input.results_whitelist =
+                        Some(self.result_whitelist.clone().unwrap_or_default());
+                }
+                "result_blacklist" => {
+                    input.results_blacklist =
+                        Some(self.result_blacklist.clone().unwrap_or_default());
+                }
+                "retry_settings" => {
+                    input.retry_settings = Some(self.retry_settings.clone().unwrap_or_default());
+                }
+                "upload_results_on_cancellation" => {
+                    input.upload_results_on_cancellation =
+                        Some(self.upload_results_on_cancellation);
+                }
+                immutable => {
+                    error!("Task field {} cannot be changed once submitted", immutable);
+                    return Err(Error::ImmutableField(immutable));
+                }
+            }
+        }
+        Ok(Some(input))
+    }
+
     /// Push changes to the struct to the compute API
-    /// This results in PUT /task/{uuid}
-    pub async fn commit(&self) -> Result<(), ComputeError> {
-        if let Some(uuid) = self.uuid {
-            let input = TaskUpdateInput {
-                constants: self.constants.clone(),
-                constraints: self.constraints.clone(),
-                tags: self.tags.clone(),
-            };
-            self.compute_client.put_update_task(uuid, input).await
-        } else {
-            Ok(())
+    /// This results in PUT /task/{uuid}, only with the modified fields
+    /// # Errors
+    /// * `Error::ImmutableField(_)` - A field that cannot be updated was modified,
+    ///   nothing is sent
+    /// * `Error::Compute(_)` - The update was rejected
+    pub async fn commit(&mut self) -> Result<(), Error> {
+        let Some(uuid) = self.uuid else {
+            return Ok(());
+        };
+        if let Some(input) = self.update_input()? {
+            self.compute_client.put_update_task(uuid, input).await?;
+            self.committed = Some(self.field_values());
         }
+        Ok(())
     }
 
     /// Get current stdout of the task
@@ -375,4 +1209,762 @@ impl<'a> Task<'a> {
             Ok(String::new())
         }
     }
+
+    /// Download the results of the task from its result bucket
+    ///
+    /// Files already present in `local_dir` with the same content are skipped.
+    ///
+    /// # Arguments
+    /// * `local_dir` - Destination directory, created if needed
+    /// * `options` - Filters on the results to download
+    /// # Errors
+    /// * `Error::NoStorageClient` - The task was not created with a storage client
+    /// * `Error::NoSuchBucket` - The task has no result bucket
+    /// * `Error::Storage(_)` - The result bucket could not be listed
+    pub async fn download_results(
+        &self,
+        local_dir: impl AsRef<Path>,
+        options: &DownloadResultsOptions,
+    ) -> Result<SyncReport, Error> {
+        let storage = self.storage.ok_or(Error::NoStorageClient)?;
+        let Some(bucket) = self.result_bucket.as_deref() else {
+            error!("Task {:?} has no result bucket", self.uuid);
+            return Err(Error::NoSuchBucket);
+        };
+        let prefix = options
+            .prefix
+            .as_deref()
+            .or(self.results_bucket_prefix.as_deref())
+            .unwrap_or_default();
+        let instance_results = options
+            .instances
+            .as_ref()
+            .map(|ids| self.instance_results(ids));
+        let key_prefix = normalize_prefix(prefix);
+        let filter = |key: &str| {
+            instance_results.as_ref().is_none_or(|results| {
+                results.contains(key) || results.contains(&format!("{key_prefix}{key}"))
+            }) && options.glob.as_ref().is_none_or(|g| g.matches(key))
+                && options.regex.as_ref().is_none_or(|r| r.is_match(key))
+        };
+        let sync_options = SyncOptions {
+            delete_extraneous: false,
+            concurrency: options.concurrency,
+        };
+        Ok(sync_remote_to_local_filtered(
+            storage,
+            bucket,
+            prefix,
+            local_dir.as_ref(),
+            &sync_options,
+            filter,
+        )
+        .await?)
+    }
+
+    /// Download the content of the latest snapshot of the task
+    ///
+    /// Each snapshot replaces the previous one in the snapshot bucket, which
+    /// defaults to the result bucket when the task has none.
+    ///
+    /// # Arguments
+    /// * `local_dir` - Destination directory, created if needed
+    /// * `options` - Synchronization options, see [`SyncOptions`]
+    /// # Errors
+    /// * `Error::NoStorageClient` - The task was not created with a storage client
+    /// * `Error::NoSuchBucket` - The task has neither snapshot nor result bucket
+    /// * `Error::Storage(_)` - The snapshot bucket could not be listed
+    pub async fn download_snapshot(
+        &self,
+        local_dir: impl AsRef<Path>,
+        options: &SyncOptions,
+    ) -> Result<SyncReport, Error> {
+        let storage = self.storage.ok_or(Error::NoStorageClient)?;
+        let (bucket, prefix) = match (&self.snapshot_bucket, &self.result_bucket) {
+            (Some(bucket), _) => (bucket, &self.snapshot_bucket_prefix),
+            (None, Some(bucket)) => (bucket, &self.results_bucket_prefix),
+            (None, None) => {
+                error!("Task {:?} has no snapshot bucket", self.uuid);
+                return Err(Error::NoSuchBucket);
+            }
+        };
+        Ok(sync_remote_to_local(
+            storage,
+            bucket,
+            prefix.as_deref().unwrap_or_default(),
+            local_dir.as_ref(),
+            options,
+        )
+        .await?)
+    }
+
+    /// Result paths of the given completed instances
+    fn instance_results(&self, instance_ids: &[i32]) -> HashSet<String> {
+        self.completed_instances
+            .iter()
+            .flatten()
+            .filter(|i| i.instance_id.is_some_and(|id| instance_ids.contains(&id)))
+            .flat_map(|i| i.results.iter().flatten())
+            .map(|r| r.trim_start_matches('/').to_owned())
+            .collect()
+    }
+}
+
+/// Format a duration as a .NET `TimeSpan`, as expected by the API: `[d.]hh:mm:ss`
+fn format_time_span(duration: TimeDelta) -> String {
+    let seconds = duration.num_seconds().max(0);
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+    let time = format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    if days > 0 {
+        format!("{days}.{time}")
+    } else {
+        time
+    }
+}
+
+/// Parse a .NET `TimeSpan` returned by the API, fractions of seconds are dropped
+fn parse_time_span(span: &str) -> Option<TimeDelta> {
+    let (days, time) = match span.split_once('.') {
+        Some((days, time)) if !days.contains(':') => (days.parse().ok()?, time),
+        _ => (0, span),
+    };
+    let time = time.split('.').next()?;
+    let mut parts = time.split(':').map(str::parse::<i64>);
+    let (hours, minutes, seconds) = (
+        parts.next()?.ok()?,
+        parts.next()?.ok()?,
+        parts.next()?.ok()?,
+    );
+    TimeDelta::try_seconds(((days * 24 + hours) * 60 + minutes) * 60 + seconds)
+}
+
+/// Constants as a map sorted by key, so that equal constants compare equal
+/// whatever their order
+fn constants_value(constants: Option<&Constants>) -> serde_json::Value {
+    serde_json::json!(constants.map(|c| c.0.iter().collect::<BTreeMap<_, _>>()))
+}
+
+/// Values of the tracked fields received from the API, applied by
+/// [`Task::update_fields`] to the fields without local changes
+struct FieldRefresh {
+    dirty: Vec<&'static str>,
+    committed: HashMap<&'static str, serde_json::Value>,
+}
+
+impl FieldRefresh {
+    fn set<T: Serialize>(&mut self, name: &'static str, field: &mut T, received: T) {
+        let value = serde_json::json!(received);
+        self.set_as(name, field, received, value);
+    }
+
+    /// Like `set`, for fields tracked under another representation
+    fn set_as<T>(
+        &mut self,
+        name: &'static str,
+        field: &mut T,
+        received: T,
+        value: serde_json::Value,
+    ) {
+        self.committed.insert(name, value);
+        if !self.dirty.contains(&name) {
+            *field = received;
+        }
+    }
+}
+
+/// State of a [`Task::watch`] stream between two updates
+struct TaskWatch<'t, 'a> {
+    task: &'t mut Task<'a>,
+    snapshot: TaskSnapshot,
+    pending: VecDeque<TaskEvent>,
+    first: bool,
+    done: bool,
+}
+
+impl<'t, 'a> TaskWatch<'t, 'a> {
+    fn new(task: &'t mut Task<'a>) -> Self {
+        Self {
+            task,
+            snapshot: TaskSnapshot::default(),
+            pending: VecDeque::new(),
+            first: true,
+            done: false,
+        }
+    }
+
+    /// Queue the changes of the freshly updated task, the watch is done once
+    /// the task reaches a terminal state
+    fn observe(&mut self) {
+        self.pending.extend(self.snapshot.update(self.task));
+        self.done = self.task.state.as_ref().is_some_and(State::is_terminal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::test_utils::{compute_client, task};
+    use crate::storage::LocalStorageClient;
+
+    fn completed_instance(instance_id: i32, results: &[&str]) -> CompletedFrameOutput {
+        serde_json::from_value(serde_json::json!({
+            "instanceId": instance_id,
+            "results": results,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn state_history_from_updates() {
+        let compute_client = compute_client();
+        let mut task = task(&compute_client, "task");
+        let update = |state: &str, previous: &str, time: &str, previous_time: &str| {
+            serde_json::from_value::<TaskOutput>(serde_json::json!({
+                "state": state,
+                "previousState": previous,
+                "stateTransitionTime": time,
+                "previousStateTransitionTime": previous_time,
+            }))
+            .unwrap()
+        };
+        task.update_fields(update(
+            "FullyExecuting",
+            "Submitted",
+            "2024-05-02T10:01:00Z",
+            "2024-05-02T10:00:00Z",
+        ));
+        task.update_fields(update(
+            "Hibernating",
+            "FullyExecuting",
+            "2024-05-02T10:05:00Z",
+            "2024-05-02T10:01:00Z",
+        ));
+        task.update_fields(update(
+            "Success",
+            "Hibernating",
+            "2024-05-02T10:09:00Z",
+            "0001-01-01T00:00:00Z",
+        ));
+        let states: Vec<String> = task
+            .state_history
+            .iter()
+            .map(|t| t.state.to_string())
+            .collect();
+        assert_eq!(
+            states,
+            vec!["Submitted", "FullyExecuting", "Hibernating", "Success"]
+        );
+        assert_eq!(task.previous_state, Some(State::from("Hibernating")));
+        assert!(task.state.as_ref().is_some_and(State::is_success));
+    }
+
+    #[test]
+    fn commit_sends_modified_fields_only() {
+        let compute_client = compute_client();
+        let mut task = task(&compute_client, "task");
+        task.tags = Some(vec![String::from("render")]);
+        assert!(!task.is_dirty());
+        task.update_fields(
+            serde_json::from_value(serde_json::json!({
+                "profile": "docker-batch",
+                "tags": ["render"],
+            }))
+            .unwrap(),
+        );
+        assert!(!task.is_dirty());
+        assert!(task.update_input().unwrap().is_none());
+
+        task.labels = Some(HashMap::from([(String::from("team"), String::from("a"))]));
+        task.tags = None;
+        task.upload_results_on_cancellation = true;
+        assert_eq!(
+            task.dirty_fields(),
+            vec!["labels", "tags", "upload_results_on_cancellation"]
+        );
+        let input = task.update_input().unwrap().unwrap();
+        assert_eq!(
+            serde_json::to_value(input).unwrap(),
+            serde_json::json!({
+                "labels": {"team": "a"},
+                "tags": [],
+                "uploadResultsOnCancellation": true,
+            })
+        );
+
+        task.profile = Some(String::from("docker-network"));
+        assert!(matches!(
+            task.update_input(),
+            Err(Error::ImmutableField("profile"))
+        ));
+    }
+
+    #[test]
+    fn constants_order_is_not_a_change() {
+        let compute_client = compute_client();
+        let mut task = task(&compute_client, "task");
+        let names: Vec<String> = (0..32).map(|i| format!("KEY_{i}")).collect();
+        let constants = |keys: &mut dyn Iterator<Item = &String>| {
+            let mut constants = Constants::new();
+            for key in keys {
+                constants.insert(key, "value");
+            }
+            constants
+        };
+        task.constants = Some(constants(&mut names.iter()));
+        let remote: Vec<_> = names
+            .iter()
+            .rev()
+            .map(|k| serde_json::json!({"key": k, "value": "value"}))
+            .collect();
+        let output = serde_json::json!({
+            "constants": remote,
+            "snapshotWhitelist": "*.ckpt",
+            "retrySettings": {"maxTotalRetries": 2},
+        });
+        task.update_fields(serde_json::from_str(&output.to_string()).unwrap());
+        task.constants = Some(constants(&mut names.iter().rev()));
+        assert!(!task.is_dirty());
+        assert_eq!(task.snapshot_whitelist.as_deref(), Some("*.ckpt"));
+        assert_eq!(
+            task.retry_settings
+                .as_ref()
+                .and_then(|r| r.max_total_retries),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn updates_keep_local_changes() {
+        let compute_client = compute_client();
+        let mut task = task(&compute_client, "task");
+        task.update_fields(
+            serde_json::from_value(serde_json::json!({
+                "tags": ["render"],
+                "labels": {"team": "a"},
+            }))
+            .unwrap(),
+        );
+        task.tags = Some(vec![String::from("render"), String::from("urgent")]);
+
+        task.update_fields(
+            serde_json::from_value(serde_json::json!({
+                "tags": ["render"],
+                "labels": {"team": "b"},
+                "resultsWhitelist": "*.png",
+            }))
+            .unwrap(),
+        );
+        assert_eq!(
+            task.tags,
+            Some(vec![String::from("render"), String::from("urgent")])
+        );
+        assert_eq!(task.labels.as_ref().map(|l| l["team"].as_str()), Some("b"));
+        assert_eq!(task.result_whitelist.as_deref(), Some("*.png"));
+        assert_eq!(task.dirty_fields(), vec!["tags"]);
+        let input = task.update_input().unwrap().unwrap();
+        assert_eq!(
+            serde_json::to_value(input).unwrap(),
+            serde_json::json!({"tags": ["render", "urgent"]})
+        );
+    }
+
+    #[tokio::test]
+    async fn resubmit_failed_instances() {
+        let compute_client = compute_client();
+        let mut task = Task::new(
+            &compute_client,
+            "task",
+            "docker-batch".into(),
+            Some(String::from("render")),
+            8.into(),
+        );
+        let uuid = uuid::Uuid::new_v4();
+        task.uuid = Some(uuid);
+        task.state = Some(State::Failure);
+        task.status = serde_json::from_value(serde_json::json!({
+            "failedRange": "2-3,6",
+        }))
+        .unwrap();
+        let failed = task.failed_instances();
+        assert_eq!(failed.to_string(), "2-3,6");
+
+        // Rejected before reaching the API: the wrapper keeps the failed task
+        task.pool_uuid = Some(uuid::Uuid::new_v4());
+        assert!(matches!(
+            task.resubmit(&failed).await,
+            Err(Error::Validation(_))
+        ));
+        assert_eq!(task.uuid, Some(uuid));
+        assert_eq!(task.shortname.as_deref(), Some("render"));
+        assert_eq!(task.instance_count, Some(8));
+        assert_eq!(task.advanced_range, None);
+
+        task.forget_execution();
+        assert!(task.state.is_none() && task.failed_instances().is_empty());
+    }
+
+    #[tokio::test]
+    async fn retry_failed_reports_the_redone_instances() {
+        let mut server = mockito::Server::new_async().await;
+        let compute_client = ComputeClient::new(server.url(), String::from("v1"), "key").unwrap();
+        for (strategy, route) in [
+            (RetryStrategy::Retry, "retry"),
+            (RetryStrategy::Recover, "recover"),
+            (RetryStrategy::Resume, "resume"),
+        ] {
+            let uuid = uuid::Uuid::new_v4();
+            let new_uuid = uuid::Uuid::new_v4();
+            server
+                .mock("GET", format!("/v1/tasks/{uuid}").as_str())
+                .with_body(
+                    serde_json::json!({
+                        "uuid": uuid,
+                        "state": "Failure",
+                        "instanceCount": 4,
+                        "status": {"failedRange": "1,3"},
+                    })
+                    .to_string(),
+                )
+                .create_async()
+                .await;
+            let redo = server
+                .mock("POST", format!("/v1/tasks/{uuid}/{route}").as_str())
+                .with_body(serde_json::json!({ "uuid": new_uuid }).to_string())
+                .expect(1)
+                .create_async()
+                .await;
+            server
+                .mock("GET", format!("/v1/tasks/{new_uuid}").as_str())
+                .with_body(
+                    serde_json::json!({
+                        "uuid": new_uuid,
+                        "state": "Submitted",
+                        "advancedRanges": "1,3",
+                    })
+                    .to_string(),
+                )
+                .create_async()
+                .await;
+            let mut task = task(&compute_client, "task");
+            task.uuid = Some(uuid);
+
+            let redone = task.retry_failed(strategy).await.unwrap();
+            redo.assert_async().await;
+            assert_eq!(redone.to_string(), "1,3");
+            assert_eq!(task.uuid, Some(new_uuid));
+            assert_eq!(task.state, Some(State::Submitted));
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_failed_needs_a_new_uuid() {
+        let mut server = mockito::Server::new_async().await;
+        let uuid = uuid::Uuid::new_v4();
+        server
+            .mock("GET", format!("/v1/tasks/{uuid}").as_str())
+            .with_body(
+                serde_json::json!({"uuid": uuid, "status": {"failedRange": "0"}}).to_string(),
+            )
+            .create_async()
+            .await;
+        for route in ["retry", "clone"] {
+            server
+                .mock("POST", format!("/v1/tasks/{uuid}/{route}").as_str())
+                .with_body("{}")
+                .create_async()
+                .await;
+        }
+        let compute_client = ComputeClient::new(server.url(), String::from("v1"), "key").unwrap();
+        let mut task = task(&compute_client, "task");
+        assert!(matches!(
+            task.retry_failed(RetryStrategy::Retry).await,
+            Err(Error::Compute(ComputeError::NotSubmitted))
+        ));
+        task.uuid = Some(uuid);
+        assert!(matches!(
+            task.retry_failed(RetryStrategy::Retry).await,
+            Err(Error::Compute(ComputeError::MissingUuid(route))) if route.ends_with("/retry")
+        ));
+        assert!(matches!(
+            task.clone_as("clone", TaskCloneInput::new()).await,
+            Err(Error::Compute(ComputeError::MissingUuid(route))) if route.ends_with("/clone")
+        ));
+    }
+
+    #[test]
+    fn clone_keeps_unset_fields() {
+        let compute_client = compute_client();
+        let mut task = task(&compute_client, "sweep");
+        let mut constants = Constants::new();
+        constants.insert("DOCKER_CMD", "./simulate");
+        constants.insert("STEP", "0.1");
+        task.constants = Some(constants);
+        task.result_bucket = Some(String::from("results"));
+        task.results_bucket_prefix = Some(String::from("sweep/"));
+        task.snapshot_bucket = Some(String::from("snapshots"));
+
+        let mut overrides = TaskCloneInput::new();
+        let mut step = Constants::new();
+        step.insert("STEP", "0.2");
+        overrides.constants = Some(step);
+        overrides.snapshot_bucket = Some(String::from("other"));
+        let input = task.clone_input("sweep-0.2", overrides);
+
+        assert_eq!(input.name.as_deref(), Some("sweep-0.2"));
+        let constants = input.constants.unwrap().0;
+        assert_eq!(constants["STEP"], "0.2");
+        assert_eq!(constants["DOCKER_CMD"], "./simulate");
+        assert_eq!(input.result_bucket, None);
+        assert_eq!(input.results_bucket.as_deref(), Some("results"));
+        assert_eq!(input.results_bucket_prefix.as_deref(), Some("sweep/"));
+        assert_eq!(input.snapshot_bucket.as_deref(), Some("other"));
+        assert_eq!(input.snapshot_bucket_prefix, None);
+    }
+
+    #[test]
+    fn watch_waits_for_cancellation_to_complete() {
+        let compute_client = compute_client();
+        let mut task = task(&compute_client, "task");
+        let mut watch = TaskWatch::new(&mut task);
+
+        watch.task.state = Some(State::PendingCancel);
+        watch.observe();
+        assert!(!watch.done);
+        assert!(matches!(
+            watch.pending.pop_front(),
+            Some(TaskEvent::StateChanged {
+                previous: None,
+                current: State::PendingCancel
+            })
+        ));
+
+        watch.task.state = Some(State::Cancelled);
+        watch.observe();
+        assert!(watch.done);
+        assert!(matches!(
+            watch.pending.pop_front(),
+            Some(TaskEvent::StateChanged {
+                previous: Some(State::PendingCancel),
+                current: State::Cancelled
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn watch_needs_a_submitted_task() {
+        let compute_client = compute_client();
+        let mut task = task(&compute_client, "task");
+        let mut events = std::pin::pin!(task.watch());
+        assert!(matches!(
+            events.next().await,
+            Some(Err(ComputeError::NotSubmitted))
+        ));
+        assert!(events.next().await.is_none());
+    }
+
+    #[test]
+    fn instances_from_range_strings() {
+        let Ok(InstancesOrRange::Range(range)) = InstancesOrRange::try_from("0-9,20") else {
+            panic!("0-9,20 is a valid range");
+        };
+        assert_eq!(range.len(), 11);
+        assert!(InstancesOrRange::try_from("0-x").is_err());
+    }
+
+    #[test]
+    fn time_span_round_trip() {
+        let ttl = TimeDelta::days(2) + TimeDelta::hours(3) + TimeDelta::seconds(5);
+        assert_eq!(format_time_span(ttl), "2.03:00:05");
+        assert_eq!(format_time_span(TimeDelta::minutes(90)), "01:30:00");
+        assert_eq!(parse_time_span("2.03:00:05"), Some(ttl));
+        assert_eq!(
+            parse_time_span("01:30:00.5000000"),
+            Some(TimeDelta::minutes(90))
+        );
+        assert_eq!(parse_time_span("soon"), None);
+    }
+
+    #[tokio::test]
+    async fn wait_stops_on_cancellation() {
+        let compute_client = compute_client();
+        let mut task = task(&compute_client, "task");
+        task.uuid = Some(uuid::Uuid::new_v4());
+        let token = CancellationToken::new();
+        token.cancel();
+        let outcome = task.wait_with_cancellation(&token, false).await.unwrap();
+        assert_eq!(outcome, WaitOutcome::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn wait_with_timeout_aborts_when_asked() {
+        let mut server = mockito::Server::new_async().await;
+        let uuid = uuid::Uuid::new_v4();
+        let info = server
+            .mock("GET", format!("/v1/tasks/{uuid}").as_str())
+            .with_body(serde_json::json!({"uuid": uuid, "state": "FullyExecuting"}).to_string())
+            .expect_at_least(2)
+            .create_async()
+            .await;
+        let abort = server
+            .mock("POST", format!("/v1/tasks/{uuid}/abort").as_str())
+            .expect(1)
+            .create_async()
+            .await;
+        let compute_client = ComputeClient::new(server.url(), String::from("v1"), "key").unwrap();
+        let mut task = task(&compute_client, "task");
+        task.uuid = Some(uuid);
+        task.poll_interval = Duration::from_millis(10);
+
+        let outcome = task
+            .wait_with_timeout(Duration::from_millis(100), false)
+            .await
+            .unwrap();
+        assert_eq!(outcome, WaitOutcome::TimedOut);
+        assert!(!abort.matched_async().await);
+
+        let outcome = task
+            .wait_with_timeout(Duration::from_millis(100), true)
+            .await
+            .unwrap();
+        assert_eq!(outcome, WaitOutcome::TimedOut);
+        info.assert_async().await;
+        abort.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn forced_constants_use_the_pool_profile() {
+        let mut server = mockito::Server::new_async().await;
+        let pool_uuid = uuid::Uuid::new_v4();
+        let job_uuid = uuid::Uuid::new_v4();
+        server
+            .mock("GET", format!("/v1/jobs/{job_uuid}").as_str())
+            .with_body(serde_json::json!({"uuid": job_uuid, "poolUuid": pool_uuid}).to_string())
+            .create_async()
+            .await;
+        let pool = server
+            .mock("GET", format!("/v1/pools/{pool_uuid}").as_str())
+            .with_body(
+                serde_json::json!({"uuid": pool_uuid, "profile": "docker-batch"}).to_string(),
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/v1/profiles/docker-batch")
+            .with_body(r#"{"name": "docker-batch", "constants": [{"name": "DOCKER_CMD"}]}"#)
+            .create_async()
+            .await;
+        let compute_client = ComputeClient::new(server.url(), String::from("v1"), "key").unwrap();
+        let mut task = task(&compute_client, "task");
+        task.profile = None;
+        task.job_uuid = Some(job_uuid);
+        task.force_constant("GPU_COUNT", "1", ForcedConstantAccess::ReadOnly, false);
+
+        let Err(Error::Validation(e)) = task.check_forced_constants().await else {
+            panic!("GPU_COUNT is not declared by the pool profile");
+        };
+        assert_eq!(e.violations.len(), 1);
+        pool.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn download_results_with_filters() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = LocalStorageClient::new(root.path().join("storage"));
+        storage.create_bucket("results").await.unwrap();
+        for key in ["run/0/image.png", "run/0/log.txt", "run/1/image.png"] {
+            storage
+                .put_object("results", key, bytes::Bytes::from(key))
+                .await
+                .unwrap();
+        }
+        let compute_client = compute_client();
+        let mut task = task(&compute_client, "task");
+        let output = root.path().join("output");
+        assert!(matches!(
+            task.download_results(&output, &DownloadResultsOptions::default())
+                .await,
+            Err(Error::NoStorageClient)
+        ));
+
+        task.set_storage(Some(&storage));
+        task.result_bucket = Some(String::from("results"));
+        task.results_bucket_prefix = Some(String::from("run"));
+        task.completed_instances = Some(vec![
+            completed_instance(0, &["0/image.png", "0/log.txt"]),
+            completed_instance(1, &["run/1/image.png"]),
+        ]);
+        let options = DownloadResultsOptions {
+            instances: Some(vec![1]),
+            ..DownloadResultsOptions::default()
+        };
+        let report = task.download_results(&output, &options).await.unwrap();
+        assert_eq!(report.transferred, vec!["1/image.png"]);
+
+        let options = DownloadResultsOptions {
+            glob: Some(glob::Pattern::new("*/*.png").unwrap()),
+            ..DownloadResultsOptions::default()
+        };
+        let report = task.download_results(&output, &options).await.unwrap();
+        assert_eq!(report.transferred, vec!["0/image.png"]);
+        assert_eq!(report.skipped, vec!["1/image.png"]);
+
+        let options = DownloadResultsOptions {
+            regex: Some(regex::Regex::new(r"\.txt$").unwrap()),
+            ..DownloadResultsOptions::default()
+        };
+        let report = task.download_results(&output, &options).await.unwrap();
+        assert_eq!(report.transferred, vec!["0/log.txt"]);
+        assert_eq!(
+            std::fs::read_to_string(output.join("0/log.txt")).unwrap(),
+            "run/0/log.txt"
+        );
+    }
+
+    #[tokio::test]
+    async fn download_latest_snapshot() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = LocalStorageClient::new(root.path().join("storage"));
+        storage.create_bucket("checkpoints").await.unwrap();
+        for key in ["sim/state.bin", "other/state.bin"] {
+            storage
+                .put_object("checkpoints", key, bytes::Bytes::from(key))
+                .await
+                .unwrap();
+        }
+        let compute_client = compute_client();
+        let mut task = task(&compute_client, "task");
+        task.set_storage(Some(&storage));
+        let output = root.path().join("output");
+        assert!(matches!(
+            task.download_snapshot(&output, &SyncOptions::default())
+                .await,
+            Err(Error::NoSuchBucket)
+        ));
+
+        task.snapshot_bucket = Some(String::from("checkpoints"));
+        task.snapshot_bucket_prefix = Some(String::from("sim/"));
+        let report = task
+            .download_snapshot(&output, &SyncOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(report.transferred, vec!["state.bin"]);
+        assert_eq!(
+            std::fs::read_to_string(output.join("state.bin")).unwrap(),
+            "sim/state.bin"
+        );
+        assert!(matches!(
+            task.snapshot_now().await,
+            Err(ComputeError::NotSubmitted)
+        ));
+        assert!(matches!(
+            task.snapshot_every(Duration::from_secs(60)).await,
+            Err(ComputeError::NotSubmitted)
+        ));
+    }
 }
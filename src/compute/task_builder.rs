@@ -0,0 +1,554 @@
+use crate::compute::client::ComputeClient;
+use crate::compute::constraints::{self, Constraint, ConstraintConflict, HardwareConstraints};
+use crate::compute::models::{
+    Constants, ForcedConstantAccess, HardwareConstraintVariant, InstanceRange, Profile,
+    RetrySettings,
+};
+use crate::compute::resources::TaskResources;
+use crate::compute::task::Task;
+use crate::storage::StorageClient;
+
+use chrono::TimeDelta;
+use std::fmt;
+
+/// Maximum number of instances of a task
+pub const MAX_INSTANCES: usize = 2048;
+/// Maximum length of a task name, exclusive
+pub const MAX_NAME_LENGTH: usize = 2048;
+
+/// Rule of the task API broken by a task
+#[derive(Clone, Debug, PartialEq)]
+pub enum TaskViolation {
+    /// The name is not shorter than `MAX_NAME_LENGTH` characters
+    NameTooLong(usize),
+    /// The shortname uses other characters than `a-z`, `A-Z`, `0-9` and `-`,
+    /// starts with `-` or ends with `-`
+    InvalidShortname(String),
+    /// Both a profile and a pool are set
+    ProfileAndPool,
+    /// Neither a profile nor a pool is set, and the task is not in a job
+    MissingProfileOrPool,
+    /// A pool is set along a job, the job pool is used instead
+    PoolWithJob,
+    /// Both an instance count and an advanced range are set
+    InstanceCountAndRange,
+    /// Neither an instance count nor an advanced range is set
+    MissingInstances,
+    /// The instance count is not strictly positive
+    InvalidInstanceCount(i32),
+    /// The advanced range has no instance
+    EmptyRange,
+    /// More instances than `MAX_INSTANCES`
+    TooManyInstances(usize),
+    /// A forced constant is not declared by the profile
+    UnknownForcedConstant { constant: String, profile: String },
+    /// No hardware can meet the hardware constraints
+    ConflictingHardwareConstraints(ConstraintConflict),
+}
+
+impl fmt::Display for TaskViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NameTooLong(len) => write!(
+                f,
+                "name is {len} characters long, it should be less than {MAX_NAME_LENGTH}"
+            ),
+            Self::InvalidShortname(shortname) => write!(
+                f,
+                "shortname {shortname:?} should only contain a-z, A-Z, 0-9 and -, and not start or end with -"
+            ),
+            Self::ProfileAndPool => write!(f, "profile and pool cannot both be set"),
+            Self::MissingProfileOrPool => write!(f, "either a profile or a pool should be set"),
+            Self::PoolWithJob => write!(f, "pool should not be set when a job is set"),
+            Self::InstanceCountAndRange => {
+                write!(f, "instance count and advanced range cannot both be set")
+            }
+            Self::MissingInstances => {
+                write!(f, "either an instance count or an advanced range should be set")
+            }
+            Self::InvalidInstanceCount(count) => {
+                write!(f, "instance count should be positive, got {count}")
+            }
+            Self::EmptyRange => write!(f, "advanced range should not be empty"),
+            Self::TooManyInstances(count) => write!(
+                f,
+                "{count} instances requested, the maximum is {MAX_INSTANCES}"
+            ),
+            Self::UnknownForcedConstant { constant, profile } => write!(
+                f,
+                "forced constant {constant:?} is not a constant of profile {profile:?}"
+            ),
+            Self::ConflictingHardwareConstraints(conflict) => {
+                write!(f, "conflicting hardware constraints: {conflict}")
+            }
+        }
+    }
+}
+
+/// Every rule broken by a task, returned before submitting it
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationError {
+    pub violations: Vec<TaskViolation>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid task: ")?;
+        for (i, violation) in self.violations.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{violation}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Check the rules of the task API that do not depend on the user account
+pub(crate) fn validate(task: &Task<'_>) -> Result<(), ValidationError> {
+    let mut violations = Vec::new();
+    let name_length = task.name.chars().count();
+    if name_length >= MAX_NAME_LENGTH {
+        violations.push(TaskViolation::NameTooLong(name_length));
+    }
+    if let Some(shortname) = task.shortname.as_deref() {
+        if !is_valid_shortname(shortname) {
+            violations.push(TaskViolation::InvalidShortname(shortname.to_owned()));
+        }
+    }
+    match (&task.profile, &task.pool_uuid) {
+        (Some(_), Some(_)) => violations.push(TaskViolation::ProfileAndPool),
+        (None, None) if task.job_uuid.is_none() => {
+            violations.push(TaskViolation::MissingProfileOrPool);
+        }
+        _ => (),
+    }
+    if task.pool_uuid.is_some() && task.job_uuid.is_some() {
+        violations.push(TaskViolation::PoolWithJob);
+    }
+    let instances = match (task.instance_count, task.advanced_range.as_ref()) {
+        (Some(_), Some(_)) => {
+            violations.push(TaskViolation::InstanceCountAndRange);
+            None
+        }
+        (None, None) => {
+            violations.push(TaskViolation::MissingInstances);
+            None
+        }
+        (Some(count), None) => match usize::try_from(count) {
+            Ok(count) if count > 0 => Some(count),
+            _ => {
+                violations.push(TaskViolation::InvalidInstanceCount(count));
+                None
+            }
+        },
+        (None, Some(range)) if range.is_empty() => {
+            violations.push(TaskViolation::EmptyRange);
+            None
+        }
+        (None, Some(range)) => Some(range.len()),
+    };
+    if let Some(count) = instances.filter(|c| *c > MAX_INSTANCES) {
+        violations.push(TaskViolation::TooManyInstances(count));
+    }
+    let hardware_constraints: Vec<Constraint> = task
+        .hardware_constraints
+        .iter()
+        .flatten()
+        .filter_map(|c| Constraint::try_from(c).ok())
+        .collect();
+    violations.extend(
+        constraints::conflicts(&hardware_constraints)
+            .into_iter()
+            .map(TaskViolation::ConflictingHardwareConstraints),
+    );
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(ValidationError { violations })
+    }
+}
+
+/// Check the forced constants of the task against the constants of `profile`
+pub(crate) fn check_forced_constants(task: &Task<'_>, profile: &Profile) -> Vec<TaskViolation> {
+    let declared: Vec<&str> = profile
+        .constants
+        .iter()
+        .flatten()
+        .filter_map(|c| c.name.as_deref())
+        .collect();
+    let profile_name = profile
+        .name
+        .as_deref()
+        .or(task.profile.as_deref())
+        .unwrap_or_default();
+    task.forced_constants
+        .iter()
+        .flatten()
+        .filter_map(|c| c.constant_name.as_deref())
+        .filter(|name| !declared.contains(name))
+        .map(|name| TaskViolation::UnknownForcedConstant {
+            constant: name.to_owned(),
+            profile: profile_name.to_owned(),
+        })
+        .collect()
+}
+
+/// Shortnames use `a-z`, `A-Z`, `0-9` and `-`, and start and end with a letter or number
+fn is_valid_shortname(shortname: &str) -> bool {
+    !shortname.is_empty()
+        && !shortname.starts_with('-')
+        && !shortname.ends_with('-')
+        && shortname
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// Fluent construction of a [`Task`], validated by [`TaskBuilder::build`]
+///
+/// ```no_run
+/// # async fn example(client: &qarnot::client::QarnotClient) {
+/// let task = client
+///     .task_builder("render")
+///     .profile("docker-batch")
+///     .instance_count(4)
+///     .constant("DOCKER_CMD", "echo hello")
+///     .build()
+///     .expect("invalid task");
+/// # }
+/// ```
+pub struct TaskBuilder<'a> {
+    task: Task<'a>,
+}
+
+impl<'a> TaskBuilder<'a> {
+    pub fn new(compute_client: &'a ComputeClient, name: &str) -> Self {
+        Self {
+            task: Task::blank(compute_client, name),
+        }
+    }
+
+    /// Storage client used to upload resources and download results
+    #[must_use]
+    pub fn storage(mut self, storage: &'a dyn StorageClient) -> Self {
+        self.task.set_storage(Some(storage));
+        self
+    }
+
+    #[must_use]
+    pub fn shortname(mut self, shortname: &str) -> Self {
+        self.task.shortname = Some(shortname.to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn profile(mut self, profile: &str) -> Self {
+        self.task.profile = Some(profile.to_owned());
+        self
+    }
+
+    /// Run the task in a pool instead of using a profile
+    #[must_use]
+    pub fn pool(mut self, pool_uuid: uuid::Uuid) -> Self {
+        self.task.pool_uuid = Some(pool_uuid);
+        self
+    }
+
+    /// Attach the task to a job, the task then runs in the job pool if any
+    #[must_use]
+    pub fn job(mut self, job_uuid: uuid::Uuid) -> Self {
+        self.task.job_uuid = Some(job_uuid);
+        self
+    }
+
+    #[must_use]
+    pub fn instance_count(mut self, instance_count: i32) -> Self {
+        self.task.instance_count = Some(instance_count);
+        self
+    }
+
+    /// Advanced range of instances, such as `1-5,8`
+    #[must_use]
+    pub fn range(mut self, advanced_range: InstanceRange) -> Self {
+        self.task.advanced_range = Some(advanced_range);
+        self
+    }
+
+    #[must_use]
+    pub fn constant(mut self, key: &str, value: &str) -> Self {
+        self.task
+            .constants
+            .get_or_insert_with(Constants::new)
+            .insert(key, value);
+        self
+    }
+
+    #[must_use]
+    pub fn constants(mut self, constants: Constants) -> Self {
+        self.task.constants = Some(constants);
+        self
+    }
+
+    #[must_use]
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.task
+            .tags
+            .get_or_insert_with(Vec::new)
+            .push(tag.to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn label(mut self, key: &str, value: &str) -> Self {
+        self.task
+            .labels
+            .get_or_insert_with(Default::default)
+            .insert(key.to_owned(), value.to_owned());
+        self
+    }
+
+    /// Secret the task needs access to
+    #[must_use]
+    pub fn secret(mut self, key: &str) -> Self {
+        self.task.grant_secret(key);
+        self
+    }
+
+    /// Prefix of the secrets the task needs access to
+    #[must_use]
+    pub fn secrets_prefix(mut self, prefix: &str) -> Self {
+        self.task.grant_secrets_prefix(prefix);
+        self
+    }
+
+    /// Override a constant of the profile, see [`Task::force_constant`]
+    #[must_use]
+    pub fn force_constant(
+        mut self,
+        name: &str,
+        value: &str,
+        access: ForcedConstantAccess,
+        export_env: bool,
+    ) -> Self {
+        self.task.force_constant(name, value, access, export_env);
+        self
+    }
+
+    /// Existing bucket to use as resources
+    #[must_use]
+    pub fn resource_bucket(mut self, bucket: &str) -> Self {
+        self.task
+            .resouce_buckets
+            .get_or_insert_with(Vec::new)
+            .push(bucket.to_owned());
+        self
+    }
+
+    /// Local inputs uploaded when running the task
+    #[must_use]
+    pub fn resources(mut self, resources: TaskResources) -> Self {
+        self.task.add_resources(resources);
+        self
+    }
+
+    /// Bucket receiving the results, with an optional key prefix
+    #[must_use]
+    pub fn result_bucket(mut self, bucket: &str, prefix: Option<&str>) -> Self {
+        self.task.result_bucket = Some(bucket.to_owned());
+        self.task.results_bucket_prefix = prefix.map(str::to_owned);
+        self
+    }
+
+    #[must_use]
+    pub fn hardware_constraint(mut self, constraint: HardwareConstraintVariant) -> Self {
+        self.task
+            .hardware_constraints
+            .get_or_insert_with(Vec::new)
+            .push(constraint);
+        self
+    }
+
+    /// Typed hardware constraints, see [`constraints`]
+    #[must_use]
+    pub fn hardware_constraints(mut self, constraints: impl Into<HardwareConstraints>) -> Self {
+        let constraints: Vec<HardwareConstraintVariant> = constraints.into().into();
+        self.task
+            .hardware_constraints
+            .get_or_insert_with(Vec::new)
+            .extend(constraints);
+        self
+    }
+
+    #[must_use]
+    pub fn retry_settings(mut self, retry_settings: RetrySettings) -> Self {
+        self.task.retry_settings = Some(retry_settings);
+        self
+    }
+
+    /// Only start the task once `task_uuid` is completed
+    #[must_use]
+    pub fn depends_on(mut self, task_uuid: uuid::Uuid) -> Self {
+        self.task
+            .dependent_on
+            .get_or_insert_with(Vec::new)
+            .push(task_uuid);
+        self
+    }
+
+    /// Delete the task once completed, after `time_to_live` if set
+    #[must_use]
+    pub fn auto_delete(mut self, time_to_live: Option<TimeDelta>) -> Self {
+        self.task.auto_delete = true;
+        self.task.completion_time_to_live = time_to_live;
+        self
+    }
+
+    #[must_use]
+    pub fn upload_results_on_cancellation(mut self, upload: bool) -> Self {
+        self.task.upload_results_on_cancellation = upload;
+        self
+    }
+
+    /// Check the task against the API rules and return it
+    ///
+    /// # Errors
+    /// Every broken rule, see [`TaskViolation`]
+    pub fn build(self) -> Result<Task<'a>, ValidationError> {
+        validate(&self.task)?;
+        Ok(self.task)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::test_utils::compute_client;
+
+    #[test]
+    fn build_valid_task() {
+        let client = compute_client();
+        let task = TaskBuilder::new(&client, "render")
+            .shortname("render-1")
+            .profile("docker-batch")
+            .range("0-9,20".parse().unwrap())
+            .constant("DOCKER_CMD", "echo")
+            .tag("sweep")
+            .build()
+            .unwrap();
+        assert_eq!(task.advanced_range.map(|r| r.len()), Some(11));
+        assert_eq!(task.tags, Some(vec![String::from("sweep")]));
+    }
+
+    #[test]
+    fn every_violation_is_reported() {
+        let client = compute_client();
+        let err = TaskBuilder::new(&client, &"a".repeat(MAX_NAME_LENGTH))
+            .shortname("-render")
+            .profile("docker-batch")
+            .pool(uuid::Uuid::nil())
+            .job(uuid::Uuid::nil())
+            .instance_count(4)
+            .range(InstanceRange::from_bounds(0, 3))
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.violations,
+            vec![
+                TaskViolation::NameTooLong(MAX_NAME_LENGTH),
+                TaskViolation::InvalidShortname(String::from("-render")),
+                TaskViolation::ProfileAndPool,
+                TaskViolation::PoolWithJob,
+                TaskViolation::InstanceCountAndRange,
+            ]
+        );
+
+        let err = TaskBuilder::new(&client, "render")
+            .range(InstanceRange::from_bounds(0, 4096))
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.violations,
+            vec![
+                TaskViolation::MissingProfileOrPool,
+                TaskViolation::TooManyInstances(4097),
+            ]
+        );
+        let err = TaskBuilder::new(&client, "render")
+            .profile("docker-batch")
+            .range(InstanceRange::new())
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(err.violations, vec![TaskViolation::EmptyRange]);
+        // Tasks of a job may use the job pool
+        assert!(TaskBuilder::new(&client, "render")
+            .job(uuid::Uuid::nil())
+            .instance_count(1)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn forced_constants_against_profile() {
+        let client = compute_client();
+        let task = TaskBuilder::new(&client, "render")
+            .profile("docker-batch")
+            .instance_count(1)
+            .force_constant(
+                "DOCKER_REPO",
+                "qarnot/dev",
+                ForcedConstantAccess::ReadOnly,
+                true,
+            )
+            .force_constant(
+                "DOKCER_TAG",
+                "latest",
+                ForcedConstantAccess::ReadWrite,
+                false,
+            )
+            .build()
+            .unwrap();
+        let profile: Profile = serde_json::from_value(serde_json::json!({
+            "name": "docker-batch",
+            "constants": [
+                {"name": "DOCKER_REPO", "value": "library/ubuntu"},
+                {"name": "DOCKER_TAG", "value": "latest"},
+            ],
+        }))
+        .unwrap();
+        assert_eq!(
+            check_forced_constants(&task, &profile),
+            vec![TaskViolation::UnknownForcedConstant {
+                constant: String::from("DOKCER_TAG"),
+                profile: String::from("docker-batch"),
+            }]
+        );
+    }
+
+    #[test]
+    fn conflicting_hardware_constraints() {
+        let client = compute_client();
+        let err = TaskBuilder::new(&client, "render")
+            .profile("docker-batch")
+            .instance_count(1)
+            .hardware_constraints(Constraint::min_cores(16).and(constraints::max_cores(4)))
+            .hardware_constraints(Constraint::Gpu.no_gpu())
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.violations,
+            vec![
+                TaskViolation::ConflictingHardwareConstraints(ConstraintConflict::Cores {
+                    min: 16,
+                    max: 4
+                }),
+                TaskViolation::ConflictingHardwareConstraints(ConstraintConflict::GpuAndNoGpu),
+            ]
+        );
+    }
+}
@@ -0,0 +1,12 @@
+use crate::compute::client::ComputeClient;
+use crate::compute::task::Task;
+
+/// Compute client pointing nowhere, for tests that never reach the API
+pub(crate) fn compute_client() -> ComputeClient {
+    ComputeClient::new(String::from("http://localhost"), String::from("v1"), "key").unwrap()
+}
+
+/// Unsubmitted `docker-batch` task of 2 instances
+pub(crate) fn task<'a>(compute_client: &'a ComputeClient, name: &str) -> Task<'a> {
+    Task::new(compute_client, name, "docker-batch".into(), None, 2.into())
+}
@@ -4,6 +4,8 @@ pub mod client;
 pub mod compute;
 /// API Config
 pub mod config;
+/// Stream over paged API responses
+mod pagination;
 /// Bucket manipulation
 pub mod storage;
 
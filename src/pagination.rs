@@ -0,0 +1,47 @@
+use futures_util::{Stream, TryStreamExt};
+use std::future::Future;
+
+/// Turn a paged API into a stream of items
+///
+/// `fetch` is called with the cursor of each page, starting with `first`,
+/// and returns the page items along with the cursor of the next page, if any.
+/// Pages are only fetched as the stream is polled.
+pub(crate) fn paginate<'a, C, T, E, F, Fut>(
+    first: C,
+    fetch: F,
+) -> impl Stream<Item = Result<T, E>> + 'a
+where
+    C: 'a,
+    T: 'a,
+    E: 'a,
+    F: FnMut(C) -> Fut + 'a,
+    Fut: Future<Output = Result<(Vec<T>, Option<C>), E>> + 'a,
+{
+    futures_util::stream::try_unfold((Some(first), fetch), |(cursor, mut fetch)| async move {
+        let Some(cursor) = cursor else {
+            return Ok(None);
+        };
+        let (items, next) = fetch(cursor).await?;
+        let items = futures_util::stream::iter(items.into_iter().map(Ok));
+        Ok(Some((items, (next, fetch))))
+    })
+    .try_flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn pages_are_chained() {
+        let items: Vec<u32> = paginate(0, |offset: u32| async move {
+            let page: Vec<u32> = (offset..(offset + 3).min(7)).collect();
+            let next = (offset + 3 < 7).then_some(offset + 3);
+            Ok::<_, ()>((page, next))
+        })
+        .try_collect()
+        .await
+        .unwrap();
+        assert_eq!(items, (0..7).collect::<Vec<u32>>());
+    }
+}
@@ -1,75 +1,112 @@
-use crate::storage::{StorageClient, StorageObject};
-use aws_sdk_s3::types::Object;
-use aws_smithy_runtime_api::client::result::SdkError;
+use crate::storage::list_objects_stream;
+use crate::storage::sync::{sync_local_to_remote, sync_remote_to_local};
+use crate::storage::{
+    ObjectInfo, ObjectStream, S3StorageClient, StorageClient, StorageError, StorageObject,
+    SyncOptions, SyncReport,
+};
+use bytes::Bytes;
+use futures_util::Stream;
+use std::path::Path;
 
-pub struct Bucket<'a> {
-    client: &'a StorageClient,
+pub struct Bucket<'a, S: StorageClient + ?Sized = S3StorageClient> {
+    client: &'a S,
     pub name: String,
 }
 
-impl<'a> Bucket<'a> {
-    pub fn new(client: &'a StorageClient, name: &str) -> Self {
+impl<'a, S: StorageClient + ?Sized> Bucket<'a, S> {
+    pub fn new(client: &'a S, name: &str) -> Self {
         Self {
             client,
             name: name.to_owned(),
         }
     }
 
-    pub async fn list_objects(&self) -> Result<Vec<Object>, Error> {
-        self.client
-            .list_objects(&self.name)
-            .await
-            .map_err(Error::from)
+    pub async fn list_objects(&self) -> Result<Vec<ObjectInfo>, StorageError> {
+        self.client.list_objects(&self.name).await
     }
 
-    //TODO Proper error handling
-    pub async fn upload_object(&self, object: StorageObject) -> Result<(), Error> {
-        self.client
-            .upload_object(&self.name, object)
-            .await
-            .map_err(|_| Error::Unknown)
+    /// Stream the objects whose key starts with `prefix`, fetching them page by page
+    pub fn list_objects_stream<'b>(
+        &'b self,
+        prefix: &'b str,
+    ) -> impl Stream<Item = Result<ObjectInfo, StorageError>> + Send + 'b {
+        list_objects_stream(self.client, &self.name, prefix)
+    }
+
+    pub async fn upload_object(&self, object: StorageObject) -> Result<(), StorageError> {
+        self.client.upload_object(&self.name, object).await
+    }
+
+    /// Store `data` in object key
+    pub async fn put_object(&self, key: &str, data: Bytes) -> Result<(), StorageError> {
+        self.client.put_object(&self.name, key, data).await
     }
 
     /// Download object key to object path
-    pub async fn get_object(&self, object: StorageObject) -> Result<(), Error> {
-        let obj = self
-            .client
-            .get_object(&self.name, &object.key)
+    /// The object is streamed to disk and only replaces object path once
+    /// it has been entirely downloaded.
+    pub async fn get_object(&self, object: StorageObject) -> Result<(), StorageError> {
+        self.client
+            .download_object(&self.name, &object.key, Path::new(&object.local_path))
             .await
-            .map_err(Error::from)?;
-        let bytes = obj.collect().await;
-        match bytes {
-            Ok(bytes) => {
-                std::fs::write(object.local_path, bytes.into_bytes()).map_err(|_| Error::WriteFile)
-            }
-            Err(_) => Err(Error::CollectObjectBytes),
-        }
+            .map(|_| ())
     }
-}
 
-#[derive(Debug)]
-pub enum Error {
-    CollectObjectBytes,
-    RequestConstructionFailed,
-    InvalidResponse,
-    NoResponse,
-    NoSuchBucket,
-    NoSuchObject,
-    ServiceError,
-    Timeout,
-    WriteFile,
-    Unknown,
-}
+    /// Returns a reader over the content of object key
+    pub async fn get_object_stream(&self, key: &str) -> Result<ObjectStream, StorageError> {
+        self.client.get_object_stream(&self.name, key).await
+    }
 
-impl<E, R> From<SdkError<E, R>> for Error {
-    fn from(e: SdkError<E, R>) -> Self {
-        match e {
-            SdkError::ConstructionFailure(_) => Self::RequestConstructionFailed,
-            SdkError::DispatchFailure(_) => Self::NoResponse,
-            SdkError::TimeoutError(_) => Self::Timeout,
-            SdkError::ResponseError(_) => Self::InvalidResponse,
-            SdkError::ServiceError(_) => Self::ServiceError,
-            _ => Self::Unknown,
-        }
+    pub async fn delete_object(&self, key: &str) -> Result<(), StorageError> {
+        self.client.delete_object(&self.name, key).await
+    }
+
+    /// Returns the metadata of object key
+    pub async fn head_object(&self, key: &str) -> Result<ObjectInfo, StorageError> {
+        self.client.head_object(&self.name, key).await
+    }
+
+    /// Upload the missing or outdated files of `local_dir` under `remote_prefix`
+    ///
+    /// # Arguments
+    /// * `local_dir` - Directory to upload
+    /// * `remote_prefix` - Prefix of the uploaded keys, `""` for the bucket root
+    /// * `options` - Concurrency and deletion of the extraneous objects
+    pub async fn sync_directory(
+        &self,
+        local_dir: impl AsRef<Path>,
+        remote_prefix: &str,
+        options: &SyncOptions,
+    ) -> Result<SyncReport, StorageError> {
+        sync_local_to_remote(
+            self.client,
+            &self.name,
+            local_dir.as_ref(),
+            remote_prefix,
+            options,
+        )
+        .await
+    }
+
+    /// Download the missing or outdated objects under `remote_prefix` to `local_dir`
+    ///
+    /// # Arguments
+    /// * `remote_prefix` - Prefix of the downloaded keys, `""` for the whole bucket
+    /// * `local_dir` - Destination directory, created if needed
+    /// * `options` - Concurrency and deletion of the extraneous files
+    pub async fn sync_remote_to_local(
+        &self,
+        remote_prefix: &str,
+        local_dir: impl AsRef<Path>,
+        options: &SyncOptions,
+    ) -> Result<SyncReport, StorageError> {
+        sync_remote_to_local(
+            self.client,
+            &self.name,
+            remote_prefix,
+            local_dir.as_ref(),
+            options,
+        )
+        .await
     }
 }
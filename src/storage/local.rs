@@ -0,0 +1,387 @@
+use crate::storage::{
+    ObjectInfo, ObjectPage, ObjectStream, StorageClient, StorageError, StorageObject,
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// [`StorageClient`] implementation storing buckets on the local filesystem
+///
+/// Every bucket is a directory of `root`, every object a file of its bucket
+/// directory. Object keys are split on `/` into subdirectories.
+/// Useful for tests or machines without access to an S3 endpoint.
+pub struct LocalStorageClient {
+    root: PathBuf,
+    page_size: usize,
+}
+
+impl LocalStorageClient {
+    /// Create a new [`LocalStorageClient`]
+    ///
+    /// # Arguments
+    /// * `root` - Directory holding the buckets, created with the first bucket
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            page_size: 1000,
+        }
+    }
+
+    /// Set the maximum number of objects of a listing page
+    pub fn set_page_size(&mut self, page_size: usize) {
+        self.page_size = page_size.max(1);
+    }
+
+    /// Directory holding the buckets
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of a bucket, whether it exists or not
+    fn bucket_path(&self, bucket: &str) -> Result<PathBuf, StorageError> {
+        if bucket.is_empty() || bucket == "." || bucket == ".." || bucket.contains(['/', '\\']) {
+            return Err(StorageError::InvalidName(bucket.to_owned()));
+        }
+        Ok(self.root.join(bucket))
+    }
+
+    /// Path of an existing bucket
+    async fn existing_bucket_path(&self, bucket: &str) -> Result<PathBuf, StorageError> {
+        let path = self.bucket_path(bucket)?;
+        match tokio::fs::metadata(&path).await {
+            Ok(meta) if meta.is_dir() => Ok(path),
+            Ok(_) => Err(StorageError::NoSuchBucket),
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(StorageError::NoSuchBucket),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Path of an object in an existing bucket
+    async fn object_path(&self, bucket: &str, key: &str) -> Result<PathBuf, StorageError> {
+        let mut path = self.existing_bucket_path(bucket).await?;
+        for part in key.split('/') {
+            if part.is_empty() || part == "." || part == ".." || part.contains('\\') {
+                return Err(StorageError::InvalidName(key.to_owned()));
+            }
+            path.push(part);
+        }
+        Ok(path)
+    }
+}
+
+/// Build the metadata of an object from its file metadata
+fn object_info(key: String, meta: &std::fs::Metadata) -> ObjectInfo {
+    ObjectInfo {
+        key,
+        size: meta.len(),
+        e_tag: None,
+        last_modified: meta.modified().ok().map(DateTime::<Utc>::from),
+    }
+}
+
+/// Map a missing file to a missing object
+fn not_found_as_no_such_object(e: std::io::Error) -> StorageError {
+    if e.kind() == ErrorKind::NotFound {
+        StorageError::NoSuchObject
+    } else {
+        StorageError::Io(e)
+    }
+}
+
+#[async_trait]
+impl StorageClient for LocalStorageClient {
+    async fn create_bucket(&self, name: &str) -> Result<(), StorageError> {
+        let path = self.bucket_path(name)?;
+        tokio::fs::create_dir_all(&self.root).await?;
+        match tokio::fs::create_dir(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => Err(StorageError::ServiceError(
+                Some(String::from("BucketAlreadyOwnedByYou")),
+            )),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn buckets(&self) -> Result<Vec<String>, StorageError> {
+        let mut entries = match tokio::fs::read_dir(&self.root).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut names = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_dir() {
+                if let Some(name) = entry.file_name().to_str() {
+                    names.push(name.to_owned());
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    async fn delete_bucket(&self, name: &str) -> Result<(), StorageError> {
+        let path = self.existing_bucket_path(name).await?;
+        match tokio::fs::remove_dir(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::DirectoryNotEmpty => Err(StorageError::ServiceError(
+                Some(String::from("BucketNotEmpty")),
+            )),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn list_objects_page(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        token: Option<String>,
+    ) -> Result<ObjectPage, StorageError> {
+        let bucket_path = self.existing_bucket_path(bucket).await?;
+        let mut objects = Vec::new();
+        let mut directories = vec![(bucket_path, String::new())];
+        while let Some((directory, directory_key)) = directories.pop() {
+            let mut entries = tokio::fs::read_dir(&directory).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+                    warn!("Skipping non UTF-8 file {:?}", entry.path());
+                    continue;
+                };
+                let key = format!("{directory_key}{name}");
+                let meta = entry.metadata().await?;
+                if meta.is_dir() {
+                    directories.push((entry.path(), format!("{key}/")));
+                } else if key.starts_with(prefix)
+                    && token.as_deref().is_none_or(|t| key.as_str() > t)
+                {
+                    objects.push(object_info(key, &meta));
+                }
+            }
+        }
+        // The token is the last key of the previous page
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        let next_token = if objects.len() > self.page_size {
+            objects.truncate(self.page_size);
+            objects.last().map(|o| o.key.clone())
+        } else {
+            None
+        };
+        Ok(ObjectPage {
+            objects,
+            next_token,
+        })
+    }
+
+    async fn upload_object(&self, bucket: &str, object: StorageObject) -> Result<(), StorageError> {
+        if !tokio::fs::metadata(&object.local_path)
+            .await
+            .is_ok_and(|m| m.is_file())
+        {
+            error!("Local file {} does not exist", object.local_path);
+            return Err(StorageError::LocalFileDoesNotExist);
+        }
+        let path = self.object_path(bucket, &object.key).await?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::copy(&object.local_path, &path).await?;
+        Ok(())
+    }
+
+    async fn put_object(&self, bucket: &str, key: &str, data: Bytes) -> Result<(), StorageError> {
+        let path = self.object_path(bucket, key).await?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, data).await?;
+        Ok(())
+    }
+
+    async fn get_object(&self, bucket: &str, key: &str) -> Result<Bytes, StorageError> {
+        let path = self.object_path(bucket, key).await?;
+        let data = tokio::fs::read(&path)
+            .await
+            .map_err(not_found_as_no_such_object)?;
+        Ok(Bytes::from(data))
+    }
+
+    async fn get_object_stream(
+        &self,
+        bucket: &str,
+        key: &str,
+    ) -> Result<ObjectStream, StorageError> {
+        let path = self.object_path(bucket, key).await?;
+        let file = tokio::fs::File::open(&path)
+            .await
+            .map_err(not_found_as_no_such_object)?;
+        Ok(Box::pin(file))
+    }
+
+    async fn delete_object(&self, bucket: &str, key: &str) -> Result<(), StorageError> {
+        let path = self.object_path(bucket, key).await?;
+        // Like S3, deleting a missing object (or a "directory") is not an error
+        match tokio::fs::symlink_metadata(&path).await {
+            Ok(meta) if !meta.is_dir() => Ok(tokio::fs::remove_file(&path).await?),
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn head_object(&self, bucket: &str, key: &str) -> Result<ObjectInfo, StorageError> {
+        let path = self.object_path(bucket, key).await?;
+        let meta = tokio::fs::metadata(&path)
+            .await
+            .map_err(not_found_as_no_such_object)?;
+        if meta.is_dir() {
+            return Err(StorageError::NoSuchObject);
+        }
+        Ok(object_info(key.to_owned(), &meta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::TryStreamExt;
+
+    #[tokio::test]
+    async fn bucket_lifecycle() {
+        let root = tempfile::tempdir().unwrap();
+        let client = LocalStorageClient::new(root.path().join("storage"));
+        assert!(client.buckets().await.unwrap().is_empty());
+
+        client.create_bucket("input").await.unwrap();
+        client.create_bucket("output").await.unwrap();
+        assert_eq!(client.buckets().await.unwrap(), vec!["input", "output"]);
+        assert!(client.create_bucket("input").await.is_err());
+
+        client.delete_bucket("output").await.unwrap();
+        assert_eq!(client.buckets().await.unwrap(), vec!["input"]);
+        assert!(matches!(
+            client.delete_bucket("output").await,
+            Err(StorageError::NoSuchBucket)
+        ));
+        assert!(matches!(
+            client.create_bucket("../escape").await,
+            Err(StorageError::InvalidName(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn object_round_trip() {
+        let root = tempfile::tempdir().unwrap();
+        let client = LocalStorageClient::new(root.path());
+        client.create_bucket("bucket").await.unwrap();
+
+        client
+            .put_object("bucket", "dir/data.txt", Bytes::from_static(b"hello"))
+            .await
+            .unwrap();
+        let local = root.path().join("local.txt");
+        std::fs::write(&local, "local file").unwrap();
+        client
+            .upload_object(
+                "bucket",
+                StorageObject::new(local.to_str().unwrap(), "local.txt"),
+            )
+            .await
+            .unwrap();
+
+        let keys: Vec<String> = client
+            .list_objects("bucket")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|o| o.key)
+            .collect();
+        assert_eq!(keys, vec!["dir/data.txt", "local.txt"]);
+
+        let info = client.head_object("bucket", "dir/data.txt").await.unwrap();
+        assert_eq!(info.size, 5);
+        assert!(info.last_modified.is_some());
+        assert_eq!(
+            client.get_object("bucket", "dir/data.txt").await.unwrap(),
+            Bytes::from_static(b"hello")
+        );
+
+        client
+            .delete_object("bucket", "dir/data.txt")
+            .await
+            .unwrap();
+        client
+            .delete_object("bucket", "dir/data.txt")
+            .await
+            .unwrap();
+        assert!(matches!(
+            client.get_object("bucket", "dir/data.txt").await,
+            Err(StorageError::NoSuchObject)
+        ));
+        assert!(matches!(
+            client.get_object("bucket", "../bucket/local.txt").await,
+            Err(StorageError::InvalidName(_))
+        ));
+        assert!(matches!(
+            client.get_object("missing", "local.txt").await,
+            Err(StorageError::NoSuchBucket)
+        ));
+    }
+
+    #[tokio::test]
+    async fn paginated_listing() {
+        let root = tempfile::tempdir().unwrap();
+        let mut client = LocalStorageClient::new(root.path());
+        client.set_page_size(2);
+        client.create_bucket("bucket").await.unwrap();
+        for key in ["a/1", "a/2", "a/3", "b/1", "c"] {
+            client
+                .put_object("bucket", key, Bytes::from_static(b"x"))
+                .await
+                .unwrap();
+        }
+
+        let page = client.list_objects_page("bucket", "", None).await.unwrap();
+        assert_eq!(page.objects.len(), 2);
+        assert_eq!(page.next_token.as_deref(), Some("a/2"));
+        assert_eq!(client.list_objects("bucket").await.unwrap().len(), 5);
+
+        let keys: Vec<String> = crate::storage::list_objects_stream(&client, "bucket", "a/")
+            .map_ok(|o| o.key)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(keys, vec!["a/1", "a/2", "a/3"]);
+    }
+
+    #[tokio::test]
+    async fn download_object_to_disk() {
+        let root = tempfile::tempdir().unwrap();
+        let client = LocalStorageClient::new(root.path().join("storage"));
+        client.create_bucket("bucket").await.unwrap();
+        let data = Bytes::from(vec![42; 200 * 1024]);
+        client
+            .put_object("bucket", "frame.exr", data.clone())
+            .await
+            .unwrap();
+
+        let target = root.path().join("out/frames/frame.exr");
+        let size = client
+            .download_object("bucket", "frame.exr", &target)
+            .await
+            .unwrap();
+        assert_eq!(size, data.len() as u64);
+        assert_eq!(std::fs::read(&target).unwrap(), data);
+
+        assert!(client
+            .download_object("bucket", "missing.exr", &target)
+            .await
+            .is_err());
+        // The previous download is kept and no temporary file is left behind
+        assert_eq!(std::fs::read(&target).unwrap(), data);
+        let files = std::fs::read_dir(target.parent().unwrap()).unwrap().count();
+        assert_eq!(files, 1);
+    }
+}
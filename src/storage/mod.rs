@@ -1,30 +1,92 @@
-use aws_sdk_s3::config::{http::HttpResponse, BehaviorVersion, Credentials, Region};
-use aws_sdk_s3::operation::{
-    create_bucket::CreateBucketError, delete_bucket::DeleteBucketError,
-    delete_object::DeleteObjectError, get_object::GetObjectError, list_buckets::ListBucketsError,
-    list_objects_v2::ListObjectsV2Error,
-};
-use aws_sdk_s3::primitives::SdkBody;
-use aws_sdk_s3::types::{Bucket, Object};
-use aws_sdk_s3::Config;
-use aws_smithy_runtime_api::{client::result::SdkError, http::Response};
-use aws_smithy_types::byte_stream::ByteStream;
-
+/// High level bucket manipulation
 pub mod bucket;
+/// Storage backend using the local filesystem
+pub mod local;
+/// Storage backend using an S3 compatible service
+pub mod s3;
+/// Directory synchronization between a local folder and a bucket
+pub mod sync;
+
+pub use local::LocalStorageClient;
+pub use s3::{MultipartConfig, S3StorageClient};
+pub use sync::{SyncOptions, SyncReport};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, TryStreamExt};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+
+/// Content of an object, read as it is downloaded
+pub type ObjectStream = Pin<Box<dyn AsyncRead + Send>>;
 
-//TODO Make a StorageClient TRAIT that can be implemented however users like,
-//then make QarnotClient<S> where S impl StorageClient, allowing multiple implems
-//of StorageClient that can use other implems of S3 libs (the horrendous aws official
-//experimental rust SDK for example).
+/// Size of the buffer used to copy an object stream to disk
+const DOWNLOAD_CHUNK_SIZE: usize = 64 * 1024;
 
+/// Errors that may happen when using a storage backend
 #[derive(Debug)]
 pub enum StorageError {
+    /// The local file to upload does not exist or cannot be read
     LocalFileDoesNotExist,
     NoSuchBucket,
+    NoSuchObject,
+    /// The bucket or object name cannot be used by the backend
+    InvalidName(String),
     UploadError,
+    /// The object content could not be retrieved
+    DownloadError,
+    /// The downloaded object could not be written locally
+    WriteFile,
+    RequestConstructionFailed,
+    NoResponse,
+    Timeout,
+    InvalidResponse,
+    /// Error returned by the storage service, with its error code if any
+    ServiceError(Option<String>),
+    Io(std::io::Error),
     Generic,
 }
 
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::LocalFileDoesNotExist => write!(f, "local file does not exist"),
+            Self::NoSuchBucket => write!(f, "no such bucket"),
+            Self::NoSuchObject => write!(f, "no such object"),
+            Self::InvalidName(name) => write!(f, "invalid name {name:?}"),
+            Self::UploadError => write!(f, "upload failed"),
+            Self::DownloadError => write!(f, "download failed"),
+            Self::WriteFile => write!(f, "could not write downloaded object"),
+            Self::RequestConstructionFailed => write!(f, "could not build storage request"),
+            Self::NoResponse => write!(f, "no response from the storage service"),
+            Self::Timeout => write!(f, "storage request timed out"),
+            Self::InvalidResponse => write!(f, "invalid response from the storage service"),
+            Self::ServiceError(Some(code)) => write!(f, "storage service error [{code}]"),
+            Self::ServiceError(None) => write!(f, "storage service error"),
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+            Self::Generic => write!(f, "storage error"),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for StorageError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
 pub struct StorageObject {
     pub local_path: String,
     pub key: String,
@@ -39,129 +101,163 @@ impl StorageObject {
     }
 }
 
-/// Lower level wrapper around S3 client
-/// Preferably use QarnotClient methods around buckets that wrap everything
-/// in higher level Bucket structurs (cf: `bucket` module)
-pub struct StorageClient {
-    s3_client: aws_sdk_s3::Client,
+/// Metadata of an object stored in a bucket
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ObjectInfo {
+    pub key: String,
+    /// Size of the object in bytes
+    pub size: u64,
+    pub e_tag: Option<String>,
+    pub last_modified: Option<DateTime<Utc>>,
 }
 
-impl StorageClient {
-    pub fn new(access_key: &str, secret_key: &str, storage_url: &str) -> Self {
-        let creds = Credentials::new(access_key, secret_key, None, None, "my_creds");
-        let config = Config::builder()
-            .endpoint_url(storage_url)
-            .endpoint_resolver(aws_sdk_s3::config::endpoint::DefaultResolver::new())
-            .credentials_provider(creds)
-            .behavior_version(BehaviorVersion::latest())
-            .region(Region::new("eu-west-1"))
-            .build();
-        let s3_client = aws_sdk_s3::Client::from_conf(config);
-        Self { s3_client }
-    }
+/// Page of a bucket listing
+#[derive(Clone, Debug, Default)]
+pub struct ObjectPage {
+    pub objects: Vec<ObjectInfo>,
+    /// Token to pass to get the next page, `None` on the last page
+    pub next_token: Option<String>,
+}
 
-    /// Provide your own s3 client
-    pub const fn new_custom_client(s3_client: aws_sdk_s3::Client) -> Self {
-        Self { s3_client }
-    }
+/// Lower level storage client
+/// Preferably use QarnotClient methods around buckets that wrap everything
+/// in higher level Bucket structures (cf: `bucket` module)
+///
+/// Implement this trait to plug another storage backend into `QarnotClient`.
+/// [`S3StorageClient`] is used by default, [`LocalStorageClient`] stores
+/// everything on the local filesystem.
+#[async_trait]
+pub trait StorageClient: Send + Sync {
+    async fn create_bucket(&self, name: &str) -> Result<(), StorageError>;
 
-    pub async fn create_bucket(
-        &self,
-        name: &str,
-    ) -> Result<(), SdkError<CreateBucketError, HttpResponse>> {
-        let res = self.s3_client.create_bucket().bucket(name).send().await;
-        match res {
-            Ok(_) => Ok(()),
-            Err(e) => Err(e),
-        }
-    }
+    /// List user bucket names
+    async fn buckets(&self) -> Result<Vec<String>, StorageError>;
 
-    /// List user buckets
-    pub async fn buckets(&self) -> Result<Vec<Bucket>, SdkError<ListBucketsError, HttpResponse>> {
-        let res = self.s3_client.list_buckets().send().await;
-        match res {
-            Ok(bucket_list) => Ok(bucket_list.buckets.unwrap_or_default()),
-            Err(e) => Err(e),
-        }
-    }
+    async fn delete_bucket(&self, name: &str) -> Result<(), StorageError>;
 
-    pub async fn delete_bucket(
-        &self,
-        name: &str,
-    ) -> Result<(), SdkError<DeleteBucketError, HttpResponse>> {
-        let res = self.s3_client.delete_bucket().bucket(name).send().await;
-        match res {
-            Ok(_) => Ok(()),
-            Err(e) => Err(e),
-        }
+    /// List every object of a bucket
+    async fn list_objects(&self, bucket: &str) -> Result<Vec<ObjectInfo>, StorageError> {
+        list_objects_stream(self, bucket, "").try_collect().await
     }
 
-    pub async fn list_objects(
+    /// List a page of the objects whose key starts with `prefix`
+    ///
+    /// # Arguments
+    /// * `bucket` - Name of the bucket
+    /// * `prefix` - Prefix of the keys to list, `""` for the whole bucket
+    /// * `token` - `next_token` of the previous page, `None` for the first page
+    async fn list_objects_page(
         &self,
         bucket: &str,
-    ) -> Result<Vec<Object>, SdkError<ListObjectsV2Error, HttpResponse>> {
-        let res = self.s3_client.list_objects_v2().bucket(bucket).send().await;
-        match res {
-            Ok(response) => Ok(response.contents().to_vec()),
-            Err(e) => Err(e),
-        }
-    }
+        prefix: &str,
+        token: Option<String>,
+    ) -> Result<ObjectPage, StorageError>;
 
-    pub async fn upload_object(
+    /// Upload the local file `object.local_path` to `object.key`
+    async fn upload_object(&self, bucket: &str, object: StorageObject) -> Result<(), StorageError>;
+
+    /// Store `data` in `key`
+    async fn put_object(&self, bucket: &str, key: &str, data: Bytes) -> Result<(), StorageError>;
+
+    /// Returns the content of an object
+    /// The whole object is held in memory, prefer `get_object_stream` or
+    /// `download_object` for large objects.
+    async fn get_object(&self, bucket: &str, key: &str) -> Result<Bytes, StorageError>;
+
+    /// Returns a reader over the content of an object
+    async fn get_object_stream(
         &self,
         bucket: &str,
-        object: StorageObject,
-    ) -> Result<(), StorageError> {
-        let stream = ByteStream::from_path(&object.local_path).await;
-        if let Ok(stream) = stream {
-            self.s3_client
-                .put_object()
-                .key(object.key)
-                .bucket(bucket)
-                .body(stream)
-                .send()
-                .await
-                .map_err(|_| StorageError::LocalFileDoesNotExist)?;
-            Ok(())
-        } else {
-            error!("{:?}", stream);
-            Err(StorageError::LocalFileDoesNotExist)
-        }
-    }
+        key: &str,
+    ) -> Result<ObjectStream, StorageError>;
 
-    pub async fn delete_object(
+    /// Download an object to `local_path`, returning its size
+    ///
+    /// The object is streamed to a temporary file next to `local_path`,
+    /// which is then renamed, so `local_path` is either left untouched or
+    /// holds the complete object. Missing parent directories are created.
+    async fn download_object(
         &self,
         bucket: &str,
         key: &str,
-    ) -> Result<(), SdkError<DeleteObjectError, HttpResponse>> {
-        let res = self
-            .s3_client
-            .delete_object()
-            .key(key)
-            .bucket(bucket)
-            .send()
-            .await;
-        match res {
-            Ok(_) => Ok(()),
+        local_path: &Path,
+    ) -> Result<u64, StorageError> {
+        let stream = self.get_object_stream(bucket, key).await?;
+        let tmp_path = temporary_path(local_path);
+        let res = write_stream(stream, &tmp_path).await;
+        let res = match res {
+            Ok(size) => tokio::fs::rename(&tmp_path, local_path)
+                .await
+                .map(|()| size)
+                .map_err(|e| {
+                    error!("Could not move {:?} to {:?}: {}", tmp_path, local_path, e);
+                    StorageError::WriteFile
+                }),
             Err(e) => Err(e),
+        };
+        if res.is_err() {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
         }
+        res
     }
 
-    pub async fn get_object(
-        &self,
-        bucket: &str,
-        key: &str,
-    ) -> Result<ByteStream, SdkError<GetObjectError, Response<SdkBody>>> {
-        let res = self
-            .s3_client
-            .get_object()
-            .bucket(bucket)
-            .key(key)
-            .send()
-            .await;
-        match res {
-            Ok(res) => Ok(res.body),
-            Err(e) => Err(e),
+    async fn delete_object(&self, bucket: &str, key: &str) -> Result<(), StorageError>;
+
+    /// Returns the metadata of an object without fetching its content
+    async fn head_object(&self, bucket: &str, key: &str) -> Result<ObjectInfo, StorageError>;
+}
+
+/// Stream the objects whose key starts with `prefix`, fetching them page by page
+///
+/// # Arguments
+/// * `client` - Storage client of the bucket
+/// * `bucket` - Name of the bucket
+/// * `prefix` - Prefix of the keys to list, `""` for the whole bucket
+pub fn list_objects_stream<'a, S: StorageClient + ?Sized>(
+    client: &'a S,
+    bucket: &'a str,
+    prefix: &'a str,
+) -> impl Stream<Item = Result<ObjectInfo, StorageError>> + Send + 'a {
+    crate::pagination::paginate(None, move |token: Option<String>| async move {
+        let page = client.list_objects_page(bucket, prefix, token).await?;
+        Ok((page.objects, page.next_token.map(Some)))
+    })
+}
+
+/// Hidden temporary file in the directory of `path`
+fn temporary_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.{:016x}.part", name, fastrand::u64(..)))
+}
+
+/// Write the content of `stream` to a new file at `path`, returning its size
+async fn write_stream(mut stream: ObjectStream, path: &Path) -> Result<u64, StorageError> {
+    let write_error = |e: std::io::Error| {
+        error!("Could not write {:?}: {}", path, e);
+        StorageError::WriteFile
+    };
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(write_error)?;
+    }
+    let mut file = tokio::fs::File::create(path).await.map_err(write_error)?;
+    let mut buf = vec![0; DOWNLOAD_CHUNK_SIZE];
+    let mut size = 0;
+    loop {
+        let read = stream.read(&mut buf).await.map_err(|e| {
+            error!("Could not download object: {}", e);
+            StorageError::DownloadError
+        })?;
+        if read == 0 {
+            break;
         }
+        file.write_all(&buf[..read]).await.map_err(write_error)?;
+        size += read as u64;
     }
+    file.sync_all().await.map_err(write_error)?;
+    Ok(size)
 }
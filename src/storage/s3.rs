@@ -0,0 +1,435 @@
+use crate::storage::{
+    ObjectInfo, ObjectPage, ObjectStream, StorageClient, StorageError, StorageObject,
+};
+
+use async_trait::async_trait;
+use aws_sdk_s3::config::{BehaviorVersion, Credentials, Region};
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+use aws_sdk_s3::Config;
+use aws_smithy_runtime_api::client::result::SdkError;
+use aws_smithy_types::byte_stream::{ByteStream, Length};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, TryStreamExt};
+
+/// Minimum size of a multipart upload part accepted by S3, except for the last one
+const MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+/// Maximum number of parts of a multipart upload accepted by S3
+const MAX_PARTS: u64 = 10_000;
+
+/// Multipart upload settings of the [`S3StorageClient`]
+///
+/// Parts are streamed from the local file, so at most `concurrency` parts
+/// are being sent at the same time.
+#[derive(Clone, Debug)]
+pub struct MultipartConfig {
+    /// Files of at least this size (in bytes) are uploaded in several parts
+    pub threshold: u64,
+    /// Size of each part in bytes, raised if needed to stay within S3 limits
+    pub part_size: u64,
+    /// Number of parts uploaded in parallel
+    pub concurrency: usize,
+}
+
+impl Default for MultipartConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 64 * 1024 * 1024,
+            part_size: 16 * 1024 * 1024,
+            concurrency: 4,
+        }
+    }
+}
+
+impl MultipartConfig {
+    /// Whether a file of `size` bytes goes through a multipart upload,
+    /// empty files always use a single `PutObject`
+    fn uses_multipart(&self, size: u64) -> bool {
+        size > 0 && size >= self.threshold
+    }
+
+    /// Split a file of `size` bytes into `(part_number, offset, length)` parts
+    fn parts(&self, size: u64) -> Vec<(i32, u64, u64)> {
+        let part_size = self
+            .part_size
+            .max(MIN_PART_SIZE)
+            .max(size.div_ceil(MAX_PARTS));
+        (0..size.div_ceil(part_size))
+            .map(|i| {
+                let offset = i * part_size;
+                let number = i32::try_from(i + 1).unwrap_or(i32::MAX);
+                (number, offset, part_size.min(size - offset))
+            })
+            .collect()
+    }
+}
+
+/// [`StorageClient`] implementation using the aws S3 SDK
+/// This is the default storage backend of `QarnotClient`
+pub struct S3StorageClient {
+    s3_client: aws_sdk_s3::Client,
+    multipart: MultipartConfig,
+}
+
+impl S3StorageClient {
+    pub fn new(access_key: &str, secret_key: &str, storage_url: &str) -> Self {
+        let creds = Credentials::new(access_key, secret_key, None, None, "my_creds");
+        let config = Config::builder()
+            .endpoint_url(storage_url)
+            .endpoint_resolver(aws_sdk_s3::config::endpoint::DefaultResolver::new())
+            .credentials_provider(creds)
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new("eu-west-1"))
+            .build();
+        let s3_client = aws_sdk_s3::Client::from_conf(config);
+        Self::new_custom_client(s3_client)
+    }
+
+    /// Provide your own s3 client
+    pub fn new_custom_client(s3_client: aws_sdk_s3::Client) -> Self {
+        Self {
+            s3_client,
+            multipart: MultipartConfig::default(),
+        }
+    }
+
+    /// Replace the multipart upload settings
+    pub fn set_multipart_config(&mut self, multipart: MultipartConfig) {
+        self.multipart = multipart;
+    }
+
+    /// Current multipart upload settings
+    pub const fn multipart_config(&self) -> &MultipartConfig {
+        &self.multipart
+    }
+
+    /// Upload a local file in several parts
+    /// The multipart upload is aborted if any part fails, so that no
+    /// incomplete upload is left behind in the bucket.
+    async fn upload_multipart(
+        &self,
+        bucket: &str,
+        key: &str,
+        local_path: &str,
+        size: u64,
+    ) -> Result<(), StorageError> {
+        let upload = self
+            .s3_client
+            .create_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await?;
+        let Some(upload_id) = upload.upload_id else {
+            error!("No upload id returned for multipart upload of {}", key);
+            return Err(StorageError::InvalidResponse);
+        };
+
+        let res = self
+            .upload_parts(bucket, key, local_path, size, &upload_id)
+            .await;
+        let res = match res {
+            Ok(parts) => self
+                .s3_client
+                .complete_multipart_upload()
+                .bucket(bucket)
+                .key(key)
+                .upload_id(&upload_id)
+                .multipart_upload(
+                    CompletedMultipartUpload::builder()
+                        .set_parts(Some(parts))
+                        .build(),
+                )
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(StorageError::from),
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = &res {
+            error!("Multipart upload of {} failed: {}", key, e);
+            if let Err(abort_err) = self
+                .s3_client
+                .abort_multipart_upload()
+                .bucket(bucket)
+                .key(key)
+                .upload_id(&upload_id)
+                .send()
+                .await
+            {
+                error!(
+                    "Could not abort multipart upload {} of {}: {:?}",
+                    upload_id, key, abort_err
+                );
+            }
+        }
+        res
+    }
+
+    /// Upload the parts of a multipart upload, returning them in order
+    async fn upload_parts(
+        &self,
+        bucket: &str,
+        key: &str,
+        local_path: &str,
+        size: u64,
+        upload_id: &str,
+    ) -> Result<Vec<CompletedPart>, StorageError> {
+        let mut parts: Vec<CompletedPart> = futures_util::stream::iter(self.multipart.parts(size))
+            .map(|(number, offset, length)| async move {
+                let body = ByteStream::read_from()
+                    .path(local_path)
+                    .offset(offset)
+                    .length(Length::Exact(length))
+                    .build()
+                    .await
+                    .map_err(|e| {
+                        error!("Could not read part {} of {}: {:?}", number, local_path, e);
+                        StorageError::UploadError
+                    })?;
+                let res = self
+                    .s3_client
+                    .upload_part()
+                    .bucket(bucket)
+                    .key(key)
+                    .upload_id(upload_id)
+                    .part_number(number)
+                    .body(body)
+                    .send()
+                    .await?;
+                debug!("Uploaded part {} of {}", number, key);
+                Ok::<_, StorageError>(
+                    CompletedPart::builder()
+                        .part_number(number)
+                        .set_e_tag(res.e_tag)
+                        .build(),
+                )
+            })
+            .buffer_unordered(self.multipart.concurrency.max(1))
+            .try_collect()
+            .await?;
+        parts.sort_by_key(|p| p.part_number);
+        Ok(parts)
+    }
+}
+
+/// Convert an aws date to a chrono one
+fn to_chrono(date: &aws_smithy_types::DateTime) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(date.secs(), date.subsec_nanos())
+}
+
+#[async_trait]
+impl StorageClient for S3StorageClient {
+    async fn create_bucket(&self, name: &str) -> Result<(), StorageError> {
+        self.s3_client.create_bucket().bucket(name).send().await?;
+        Ok(())
+    }
+
+    async fn buckets(&self) -> Result<Vec<String>, StorageError> {
+        let bucket_list = self.s3_client.list_buckets().send().await?;
+        Ok(bucket_list
+            .buckets
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|b| b.name)
+            .collect())
+    }
+
+    async fn delete_bucket(&self, name: &str) -> Result<(), StorageError> {
+        self.s3_client.delete_bucket().bucket(name).send().await?;
+        Ok(())
+    }
+
+    async fn list_objects_page(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        token: Option<String>,
+    ) -> Result<ObjectPage, StorageError> {
+        let response = self
+            .s3_client
+            .list_objects_v2()
+            .bucket(bucket)
+            .set_prefix((!prefix.is_empty()).then(|| prefix.to_owned()))
+            .set_continuation_token(token)
+            .send()
+            .await?;
+        let objects = response
+            .contents()
+            .iter()
+            .map(|o| ObjectInfo {
+                key: o.key().unwrap_or_default().to_owned(),
+                size: o.size().and_then(|s| u64::try_from(s).ok()).unwrap_or(0),
+                e_tag: o.e_tag().map(str::to_owned),
+                last_modified: o.last_modified().and_then(to_chrono),
+            })
+            .collect();
+        let next_token = if response.is_truncated().unwrap_or(false) {
+            response.next_continuation_token
+        } else {
+            None
+        };
+        Ok(ObjectPage {
+            objects,
+            next_token,
+        })
+    }
+
+    async fn upload_object(&self, bucket: &str, object: StorageObject) -> Result<(), StorageError> {
+        let size = match tokio::fs::metadata(&object.local_path).await {
+            Ok(meta) if meta.is_file() => meta.len(),
+            Ok(_) => return Err(StorageError::LocalFileDoesNotExist),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                error!("Local file {} does not exist", object.local_path);
+                return Err(StorageError::LocalFileDoesNotExist);
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if self.multipart.uses_multipart(size) {
+            return self
+                .upload_multipart(bucket, &object.key, &object.local_path, size)
+                .await;
+        }
+        let stream = ByteStream::from_path(&object.local_path)
+            .await
+            .map_err(|e| {
+                error!("Could not read {}: {:?}", object.local_path, e);
+                StorageError::UploadError
+            })?;
+        self.s3_client
+            .put_object()
+            .key(object.key)
+            .bucket(bucket)
+            .body(stream)
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    async fn put_object(&self, bucket: &str, key: &str, data: Bytes) -> Result<(), StorageError> {
+        self.s3_client
+            .put_object()
+            .key(key)
+            .bucket(bucket)
+            .body(ByteStream::from(data))
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    async fn get_object(&self, bucket: &str, key: &str) -> Result<Bytes, StorageError> {
+        let res = self
+            .s3_client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await?;
+        let bytes = res
+            .body
+            .collect()
+            .await
+            .map_err(|_| StorageError::DownloadError)?;
+        Ok(bytes.into_bytes())
+    }
+
+    async fn get_object_stream(
+        &self,
+        bucket: &str,
+        key: &str,
+    ) -> Result<ObjectStream, StorageError> {
+        let res = self
+            .s3_client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await?;
+        Ok(Box::pin(res.body.into_async_read()))
+    }
+
+    async fn delete_object(&self, bucket: &str, key: &str) -> Result<(), StorageError> {
+        self.s3_client
+            .delete_object()
+            .key(key)
+            .bucket(bucket)
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    async fn head_object(&self, bucket: &str, key: &str) -> Result<ObjectInfo, StorageError> {
+        let res = self
+            .s3_client
+            .head_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await?;
+        Ok(ObjectInfo {
+            key: key.to_owned(),
+            size: res
+                .content_length()
+                .and_then(|s| u64::try_from(s).ok())
+                .unwrap_or(0),
+            e_tag: res.e_tag().map(str::to_owned),
+            last_modified: res.last_modified().and_then(to_chrono),
+        })
+    }
+}
+
+impl<E: ProvideErrorMetadata, R> From<SdkError<E, R>> for StorageError {
+    fn from(e: SdkError<E, R>) -> Self {
+        match e {
+            SdkError::ConstructionFailure(_) => Self::RequestConstructionFailed,
+            SdkError::DispatchFailure(_) => Self::NoResponse,
+            SdkError::TimeoutError(_) => Self::Timeout,
+            SdkError::ResponseError(_) => Self::InvalidResponse,
+            SdkError::ServiceError(e) => match e.err().code() {
+                Some("NoSuchBucket") => Self::NoSuchBucket,
+                Some("NoSuchKey" | "NotFound") => Self::NoSuchObject,
+                code => Self::ServiceError(code.map(str::to_owned)),
+            },
+            _ => Self::Generic,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multipart_parts_cover_the_file() {
+        let config = MultipartConfig {
+            threshold: 0,
+            part_size: 8 * 1024 * 1024,
+            concurrency: 2,
+        };
+        let size = 20 * 1024 * 1024 + 3;
+        let parts = config.parts(size);
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0], (1, 0, 8 * 1024 * 1024));
+        assert_eq!(parts[2], (3, 16 * 1024 * 1024, 4 * 1024 * 1024 + 3));
+        assert_eq!(parts.iter().map(|p| p.2).sum::<u64>(), size);
+        assert!(config.uses_multipart(size));
+        assert!(!config.uses_multipart(0));
+        assert!(!MultipartConfig::default().uses_multipart(1024));
+    }
+
+    #[test]
+    fn multipart_parts_respect_s3_limits() {
+        let config = MultipartConfig {
+            part_size: 1024,
+            ..MultipartConfig::default()
+        };
+        assert_eq!(config.parts(12 * 1024 * 1024)[0].2, MIN_PART_SIZE);
+
+        let huge = 200 * 1024 * 1024 * 1024;
+        let parts = MultipartConfig::default().parts(huge);
+        assert!(parts.len() as u64 <= MAX_PARTS);
+        assert_eq!(parts.iter().map(|p| p.2).sum::<u64>(), huge);
+    }
+}
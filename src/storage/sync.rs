@@ -0,0 +1,506 @@
+use crate::storage::{list_objects_stream, ObjectInfo, StorageClient, StorageError, StorageObject};
+
+use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, TryStreamExt};
+use md5::{Digest, Md5};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncReadExt;
+
+/// Options of a directory synchronization
+#[derive(Clone, Debug)]
+pub struct SyncOptions {
+    /// Delete the files of the destination that are not in the source
+    pub delete_extraneous: bool,
+    /// Maximum number of files transferred at the same time
+    pub concurrency: usize,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            delete_extraneous: false,
+            concurrency: 8,
+        }
+    }
+}
+
+/// Summary of a directory synchronization
+/// Files are identified by their path relative to the synchronized
+/// directory / prefix, with `/` separators.
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    /// Files uploaded or downloaded
+    pub transferred: Vec<String>,
+    /// Files already up to date
+    pub skipped: Vec<String>,
+    /// Extraneous files deleted from the destination
+    pub deleted: Vec<String>,
+    /// Files that could not be transferred or deleted
+    pub failed: Vec<(String, StorageError)>,
+    /// Total size of the transferred files, in bytes
+    pub bytes_transferred: u64,
+}
+
+impl SyncReport {
+    /// Whether every file was synchronized
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    pub(crate) fn add_transfer(&mut self, key: String, res: Result<u64, StorageError>) {
+        match res {
+            Ok(size) => {
+                self.bytes_transferred += size;
+                self.transferred.push(key);
+            }
+            Err(e) => {
+                error!("Could not synchronize {}: {}", key, e);
+                self.failed.push((key, e));
+            }
+        }
+    }
+
+    fn add_deletion(&mut self, key: String, res: Result<(), StorageError>) {
+        match res {
+            Ok(()) => self.deleted.push(key),
+            Err(e) => {
+                error!("Could not delete {}: {}", key, e);
+                self.failed.push((key, e));
+            }
+        }
+    }
+
+    /// Add the entries of `other`, whose keys are relative to `key_prefix`
+    pub(crate) fn merge(&mut self, other: SyncReport, key_prefix: &str) {
+        let with_prefix = |key: String| format!("{key_prefix}{key}");
+        self.transferred
+            .extend(other.transferred.into_iter().map(with_prefix));
+        self.skipped
+            .extend(other.skipped.into_iter().map(with_prefix));
+        self.deleted
+            .extend(other.deleted.into_iter().map(with_prefix));
+        self.failed.extend(
+            other
+                .failed
+                .into_iter()
+                .map(|(key, e)| (with_prefix(key), e)),
+        );
+        self.bytes_transferred += other.bytes_transferred;
+    }
+
+    /// Sort the file lists, transfers finishing in any order
+    pub(crate) fn sorted(mut self) -> Self {
+        self.transferred.sort();
+        self.skipped.sort();
+        self.deleted.sort();
+        self.failed.sort_by(|a, b| a.0.cmp(&b.0));
+        self
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Upload,
+    Download,
+}
+
+struct LocalFile {
+    path: PathBuf,
+    size: u64,
+    modified: Option<DateTime<Utc>>,
+}
+
+/// Prefix of the remote keys, ending with `/` unless empty
+pub(crate) fn normalize_prefix(prefix: &str) -> String {
+    let prefix = prefix.trim_start_matches('/');
+    if prefix.is_empty() || prefix.ends_with('/') {
+        prefix.to_owned()
+    } else {
+        format!("{prefix}/")
+    }
+}
+
+/// Local path of a relative key, refusing keys escaping `dir`
+fn local_path(dir: &Path, relative_key: &str) -> Result<PathBuf, StorageError> {
+    let mut path = dir.to_path_buf();
+    for part in relative_key.split('/') {
+        if part.is_empty() || part == "." || part == ".." {
+            return Err(StorageError::InvalidName(relative_key.to_owned()));
+        }
+        path.push(part);
+    }
+    Ok(path)
+}
+
+/// Files of a local directory, by path relative to the directory
+async fn local_files(dir: &Path) -> Result<BTreeMap<String, LocalFile>, StorageError> {
+    let mut files = BTreeMap::new();
+    let mut directories = vec![(dir.to_path_buf(), String::new())];
+    while let Some((directory, prefix)) = directories.pop() {
+        let mut entries = tokio::fs::read_dir(&directory).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+                warn!("Skipping non UTF-8 file {:?}", entry.path());
+                continue;
+            };
+            let key = format!("{prefix}{name}");
+            let meta = entry.metadata().await?;
+            if meta.is_dir() {
+                directories.push((entry.path(), format!("{key}/")));
+            } else {
+                let file = LocalFile {
+                    path: entry.path(),
+                    size: meta.len(),
+                    modified: meta.modified().ok().map(DateTime::<Utc>::from),
+                };
+                files.insert(key, file);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Objects under `prefix`, by key relative to the prefix
+async fn remote_objects<S: StorageClient + ?Sized>(
+    client: &S,
+    bucket: &str,
+    prefix: &str,
+) -> Result<BTreeMap<String, ObjectInfo>, StorageError> {
+    list_objects_stream(client, bucket, prefix)
+        .try_filter_map(|o| async move {
+            let relative = o.key.strip_prefix(prefix).map(str::to_owned);
+            Ok(relative
+                .filter(|r| !r.is_empty() && !r.ends_with('/'))
+                .map(|r| (r, o)))
+        })
+        .try_collect()
+        .await
+}
+
+/// Hex md5 of a local file
+async fn file_md5(path: &Path) -> Result<String, std::io::Error> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Md5::new();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// ETag of an object if it is the md5 of its content
+/// Multipart uploads have an ETag of the form `<md5 of md5s>-<parts>`,
+/// which cannot be compared to a local file.
+fn md5_etag(object: &ObjectInfo) -> Option<String> {
+    let etag = object.e_tag.as_deref()?.trim_matches('"');
+    (etag.len() == 32 && etag.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| etag.to_ascii_lowercase())
+}
+
+/// Whether the destination of the transfer is already up to date
+///
+/// Sizes are compared first, then the content md5 when the ETag holds one,
+/// and finally the modification dates: the destination is up to date if it
+/// was modified after the source.
+async fn is_unchanged(local: &LocalFile, remote: &ObjectInfo, direction: Direction) -> bool {
+    if local.size != remote.size {
+        return false;
+    }
+    if let Some(etag) = md5_etag(remote) {
+        return file_md5(&local.path).await.is_ok_and(|md5| md5 == etag);
+    }
+    match (local.modified, remote.last_modified, direction) {
+        (Some(local), Some(remote), Direction::Upload) => local <= remote,
+        (Some(local), Some(remote), Direction::Download) => remote <= local,
+        _ => false,
+    }
+}
+
+/// Upload the files of `local_dir` that are missing or outdated under `remote_prefix`
+///
+/// # Arguments
+/// * `client` - Storage client of the bucket
+/// * `bucket` - Name of the destination bucket
+/// * `local_dir` - Directory to upload
+/// * `remote_prefix` - Prefix of the uploaded keys, `""` for the bucket root
+/// * `options` - Synchronization options
+/// # Errors
+/// Only listing failures are returned, transfer failures are in the report
+pub async fn sync_local_to_remote<S: StorageClient + ?Sized>(
+    client: &S,
+    bucket: &str,
+    local_dir: &Path,
+    remote_prefix: &str,
+    options: &SyncOptions,
+) -> Result<SyncReport, StorageError> {
+    let prefix = normalize_prefix(remote_prefix);
+    if !tokio::fs::metadata(local_dir)
+        .await
+        .is_ok_and(|m| m.is_dir())
+    {
+        error!("Local directory {:?} does not exist", local_dir);
+        return Err(StorageError::LocalFileDoesNotExist);
+    }
+    let locals = local_files(local_dir).await?;
+    let mut remotes = remote_objects(client, bucket, &prefix).await?;
+
+    let mut report = SyncReport::default();
+    let mut uploads = Vec::new();
+    for (key, local) in locals {
+        match remotes.remove(&key) {
+            Some(remote) if is_unchanged(&local, &remote, Direction::Upload).await => {
+                report.skipped.push(key);
+            }
+            _ => uploads.push((key, local)),
+        }
+    }
+
+    let prefix = prefix.as_str();
+    let mut results = futures_util::stream::iter(uploads)
+        .map(|(key, local)| async move {
+            let res = match local.path.to_str() {
+                Some(path) => {
+                    let object = StorageObject::new(path, &format!("{prefix}{key}"));
+                    client.upload_object(bucket, object).await
+                }
+                None => Err(StorageError::InvalidName(key.clone())),
+            };
+            (key, res.map(|()| local.size))
+        })
+        .buffer_unordered(options.concurrency.max(1));
+    while let Some((key, res)) = results.next().await {
+        report.add_transfer(key, res);
+    }
+
+    if options.delete_extraneous {
+        let mut results = futures_util::stream::iter(remotes.into_values())
+            .map(|remote| async move {
+                let res = client.delete_object(bucket, &remote.key).await;
+                (remote.key[prefix.len()..].to_owned(), res)
+            })
+            .buffer_unordered(options.concurrency.max(1));
+        while let Some((key, res)) = results.next().await {
+            report.add_deletion(key, res);
+        }
+    }
+    Ok(report.sorted())
+}
+
+/// Upload `local_file` to `key` unless the object is already up to date
+///
+/// Returns the number of bytes uploaded, `None` if the upload was skipped.
+pub(crate) async fn sync_file_to_remote<S: StorageClient + ?Sized>(
+    client: &S,
+    bucket: &str,
+    local_file: &Path,
+    key: &str,
+) -> Result<Option<u64>, StorageError> {
+    let meta = match tokio::fs::metadata(local_file).await {
+        Ok(meta) if meta.is_file() => meta,
+        _ => {
+            error!("Local file {:?} does not exist", local_file);
+            return Err(StorageError::LocalFileDoesNotExist);
+        }
+    };
+    let local = LocalFile {
+        path: local_file.to_path_buf(),
+        size: meta.len(),
+        modified: meta.modified().ok().map(DateTime::<Utc>::from),
+    };
+    if let Ok(remote) = client.head_object(bucket, key).await {
+        if is_unchanged(&local, &remote, Direction::Upload).await {
+            return Ok(None);
+        }
+    }
+    let path = local_file
+        .to_str()
+        .ok_or_else(|| StorageError::InvalidName(local_file.to_string_lossy().into_owned()))?;
+    client
+        .upload_object(bucket, StorageObject::new(path, key))
+        .await?;
+    Ok(Some(local.size))
+}
+
+/// Download the objects under `remote_prefix` that are missing or outdated in `local_dir`
+///
+/// # Arguments
+/// * `client` - Storage client of the bucket
+/// * `bucket` - Name of the source bucket
+/// * `remote_prefix` - Prefix of the downloaded keys, `""` for the whole bucket
+/// * `local_dir` - Destination directory, created if needed
+/// * `options` - Synchronization options
+/// # Errors
+/// Only listing failures are returned, transfer failures are in the report
+pub async fn sync_remote_to_local<S: StorageClient + ?Sized>(
+    client: &S,
+    bucket: &str,
+    remote_prefix: &str,
+    local_dir: &Path,
+    options: &SyncOptions,
+) -> Result<SyncReport, StorageError> {
+    sync_remote_to_local_filtered(client, bucket, remote_prefix, local_dir, options, |_| true).await
+}
+
+/// Same as [`sync_remote_to_local`], restricted to the keys relative to
+/// `remote_prefix` accepted by `filter`
+pub(crate) async fn sync_remote_to_local_filtered<S: StorageClient + ?Sized>(
+    client: &S,
+    bucket: &str,
+    remote_prefix: &str,
+    local_dir: &Path,
+    options: &SyncOptions,
+    filter: impl Fn(&str) -> bool,
+) -> Result<SyncReport, StorageError> {
+    let prefix = normalize_prefix(remote_prefix);
+    let mut remotes = remote_objects(client, bucket, &prefix).await?;
+    remotes.retain(|key, _| filter(key));
+    tokio::fs::create_dir_all(local_dir).await?;
+    let mut locals = local_files(local_dir).await?;
+    locals.retain(|key, _| filter(key));
+
+    let mut report = SyncReport::default();
+    let mut downloads = Vec::new();
+    for (key, remote) in remotes {
+        match locals.remove(&key) {
+            Some(local) if is_unchanged(&local, &remote, Direction::Download).await => {
+                report.skipped.push(key);
+            }
+            _ => downloads.push((key, remote)),
+        }
+    }
+
+    let mut results = futures_util::stream::iter(downloads)
+        .map(|(key, remote)| async move {
+            let res = match local_path(local_dir, &key) {
+                Ok(path) => client.download_object(bucket, &remote.key, &path).await,
+                Err(e) => Err(e),
+            };
+            (key, res)
+        })
+        .buffer_unordered(options.concurrency.max(1));
+    while let Some((key, res)) = results.next().await {
+        report.add_transfer(key, res);
+    }
+
+    if options.delete_extraneous {
+        for (key, local) in locals {
+            let res = tokio::fs::remove_file(&local.path)
+                .await
+                .map_err(StorageError::from);
+            report.add_deletion(key, res);
+        }
+    }
+    Ok(report.sorted())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::LocalStorageClient;
+
+    #[test]
+    fn etag_and_prefix_normalization() {
+        let mut object = ObjectInfo {
+            e_tag: Some(String::from("\"9E107D9D372BB6826BD81D3542A419D6\"")),
+            ..ObjectInfo::default()
+        };
+        assert_eq!(
+            md5_etag(&object).as_deref(),
+            Some("9e107d9d372bb6826bd81d3542a419d6")
+        );
+        object.e_tag = Some(String::from("\"d41d8cd98f00b204e9800998ecf8427e-3\""));
+        assert_eq!(md5_etag(&object), None);
+
+        assert_eq!(normalize_prefix(""), "");
+        assert_eq!(normalize_prefix("/renders"), "renders/");
+        assert_eq!(normalize_prefix("renders/"), "renders/");
+    }
+
+    #[tokio::test]
+    async fn sync_both_ways() {
+        let root = tempfile::tempdir().unwrap();
+        let client = LocalStorageClient::new(root.path().join("storage"));
+        client.create_bucket("bucket").await.unwrap();
+        let input = root.path().join("input");
+        std::fs::create_dir_all(input.join("scenes")).unwrap();
+        std::fs::write(input.join("main.blend"), "scene").unwrap();
+        std::fs::write(input.join("scenes/a.blend"), "a").unwrap();
+        let options = SyncOptions::default();
+
+        let report = sync_local_to_remote(&client, "bucket", &input, "job", &options)
+            .await
+            .unwrap();
+        assert_eq!(report.transferred, vec!["main.blend", "scenes/a.blend"]);
+        assert_eq!(report.bytes_transferred, 6);
+
+        std::fs::write(input.join("scenes/a.blend"), "changed").unwrap();
+        let report = sync_local_to_remote(&client, "bucket", &input, "job", &options)
+            .await
+            .unwrap();
+        assert_eq!(report.transferred, vec!["scenes/a.blend"]);
+        assert_eq!(report.skipped, vec!["main.blend"]);
+
+        let output = root.path().join("output");
+        let report = sync_remote_to_local(&client, "bucket", "job/", &output, &options)
+            .await
+            .unwrap();
+        assert!(report.is_success());
+        assert_eq!(report.transferred, vec!["main.blend", "scenes/a.blend"]);
+        assert_eq!(
+            std::fs::read_to_string(output.join("scenes/a.blend")).unwrap(),
+            "changed"
+        );
+        let report = sync_remote_to_local(&client, "bucket", "job/", &output, &options)
+            .await
+            .unwrap();
+        assert!(report.transferred.is_empty());
+        assert_eq!(report.skipped.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn sync_deletes_extraneous_files() {
+        let root = tempfile::tempdir().unwrap();
+        let client = LocalStorageClient::new(root.path().join("storage"));
+        client.create_bucket("bucket").await.unwrap();
+        client
+            .put_object("bucket", "job/stale.txt", bytes::Bytes::from("old"))
+            .await
+            .unwrap();
+        client
+            .put_object("bucket", "other/kept.txt", bytes::Bytes::from("kept"))
+            .await
+            .unwrap();
+        let input = root.path().join("input");
+        std::fs::create_dir_all(&input).unwrap();
+        std::fs::write(input.join("new.txt"), "new").unwrap();
+        let options = SyncOptions {
+            delete_extraneous: true,
+            ..SyncOptions::default()
+        };
+
+        let report = sync_local_to_remote(&client, "bucket", &input, "job", &options)
+            .await
+            .unwrap();
+        assert_eq!(report.deleted, vec!["stale.txt"]);
+        let keys: Vec<String> = client
+            .list_objects("bucket")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|o| o.key)
+            .collect();
+        assert_eq!(keys, vec!["job/new.txt", "other/kept.txt"]);
+
+        std::fs::write(input.join("local_only.txt"), "local").unwrap();
+        let report = sync_remote_to_local(&client, "bucket", "job", &input, &options)
+            .await
+            .unwrap();
+        assert_eq!(report.deleted, vec!["local_only.txt"]);
+        assert!(!input.join("local_only.txt").exists());
+    }
+}